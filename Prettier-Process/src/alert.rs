@@ -0,0 +1,296 @@
+/*
+----------------------------------------------------------------------------------------
+File used to check the alert rules of live (ex: "cpu>90 for 30s", "rss>2G", "state=DZ")
+against the samples of every tick, and to report the rules that fire: highlighted in the
+output, appended to an event log and passed to a hook command.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::live::parse_duration;
+use crate::output::Stream;
+use crate::record::parse_size;
+use ppsx::struct_proc as sp;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy)]
+enum Metric {
+    Cpu,
+    Rss,
+    Virt,
+    Threads,
+} // Figures of a LiveSample that a threshold can watch
+
+#[derive(Clone, Copy)]
+enum Compare {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+#[derive(Clone)]
+enum Condition {
+    Threshold(Metric, Compare, f64), // memory thresholds are in kB, like the samples
+    State(String),                   // state letters that fire when a process enters one of them
+}
+
+#[derive(Clone)]
+pub struct Rule {
+    text: String,
+    condition: Condition,
+    hold: Option<Duration>, // the condition must stay true this long before firing ("for 30s")
+} // One --alert rule
+
+struct Episode {
+    since: Instant,
+    fired: bool,
+} // A process for which the condition of a rule currently holds
+
+pub struct Alerts {
+    rules: Vec<Rule>,
+    episodes: HashMap<(usize, usize), Episode>, // (rule index, pid)
+    log: Option<File>,
+    command: Option<String>,
+    hooks: Vec<Child>, // hook commands still running
+} // Alert rules of a live session and what they fired
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_rule:    -input:         a rule as "<metric><op><value> [for <duration>]" with metric cpu (in %), rss or
+                                        virt (size, ex: "2G") or threads and op >, >=, < or <=; or as "state=<letters>"
+                                        (ex: "state=DZ")
+                        -output:        the matching Rule; an Error message if invalid
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_rule(text: &str) -> Result<Rule, String> {
+    let text = text.trim();
+    let (condition, hold) = match text.split_once(" for ") {
+        Some((condition, hold)) => (condition.trim(), Some(parse_duration(hold)?)),
+        None => (text, None),
+    };
+    if let Some(states) = condition.strip_prefix("state=") {
+        if states.is_empty() {
+            return Err(format!("invalid alert: {} (expected state letters, ex: state=DZ)", text));
+        }
+        return Ok(Rule { text: text.to_string(), condition: Condition::State(states.to_uppercase()), hold });
+    }
+
+    let split = condition.find(['<', '>']).ok_or_else(|| format!("invalid alert: {} (expected ex: cpu>90, rss>2G, state=DZ)", text))?;
+    let (metric, rest) = condition.split_at(split);
+    let (compare, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Compare::AtLeast, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Compare::AtMost, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Compare::Above, value)
+    } else {
+        (Compare::Below, &rest[1..])
+    };
+    let metric = match metric.trim() {
+        "cpu" => Metric::Cpu,
+        "rss" => Metric::Rss,
+        "virt" => Metric::Virt,
+        "threads" => Metric::Threads,
+        other => return Err(format!("invalid alert metric: {} (expected cpu, rss, virt, threads or state)", other)),
+    };
+    let value = match metric {
+        Metric::Rss | Metric::Virt => parse_size(value)? as f64 / 1024.0,
+        _ => value.trim().parse().map_err(|_| format!("invalid alert value: {}", value))?,
+    };
+    Ok(Rule { text: text.to_string(), condition: Condition::Threshold(metric, compare, value), hold })
+}
+
+impl Rule {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method check:   -input:         a sample
+                    -output:        the value of the sample that meets the condition; None if it is not met (or if the
+                                    cpu usage is not known yet)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn check(&self, sample: &sp::LiveSample) -> Option<String> {
+        match &self.condition {
+            Condition::State(states) => states.contains(sample.state.as_str()).then(|| sample.state.clone()),
+            Condition::Threshold(metric, compare, limit) => {
+                let (value, text) = match metric {
+                    Metric::Cpu => (sample.cpu_percent?, format!("{:.2}%", sample.cpu_percent?)),
+                    Metric::Rss => (sample.memory_rss_kb as f64, format!("{} kB", sample.memory_rss_kb)),
+                    Metric::Virt => (sample.memory_virtual_kb as f64, format!("{} kB", sample.memory_virtual_kb)),
+                    Metric::Threads => (sample.threads as f64, sample.threads.to_string()),
+                };
+                let met = match compare {
+                    Compare::Above => value > *limit,
+                    Compare::AtLeast => value >= *limit,
+                    Compare::Below => value < *limit,
+                    Compare::AtMost => value <= *limit,
+                };
+                met.then_some(text)
+            }
+        }
+    }
+}
+
+impl Alerts {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method new:     -input:         the rules, the event log path and the hook command (both optional)
+                    -output:        the Alerts of the session; an Error if the log cannot be opened
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn new(rules: Vec<Rule>, log: Option<&Path>, command: Option<String>) -> Result<Alerts, io::Error> {
+        let log = match log {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        Ok(Alerts { rules, episodes: HashMap::new(), log, command, hooks: Vec::new() })
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method check:   -input:         the samples of one tick and the time of the tick
+                    -output:        the events fired at this tick
+                    -description:   a rule fires once when its condition has held for its duration (at once without
+                                    "for"), then again only after the condition stopped holding; a state rule fires
+                                    when the process enters one of its states
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn check(&mut self, samples: &[sp::LiveSample], now: Instant) -> Vec<sp::AlertEvent> {
+        let mut events = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            for sample in samples {
+                let key = (index, sample.pid);
+                let Some(value) = rule.check(sample) else {
+                    self.episodes.remove(&key);
+                    continue;
+                };
+                let episode = self.episodes.entry(key).or_insert(Episode { since: now, fired: false });
+                if !episode.fired && rule.hold.is_none_or(|hold| now.duration_since(episode.since) >= hold) {
+                    episode.fired = true;
+                    events.push(sp::AlertEvent {
+                        timestamp: sample.timestamp.clone(),
+                        pid: sample.pid,
+                        name: sample.name.clone(),
+                        rule: rule.text.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method retain:  -input:         a closure telling if a pid is still followed
+                    -output:        /
+                    -description:   forget the episodes of the processes that are gone
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        self.episodes.retain(|(_, pid), _| keep(*pid));
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method report:  -input:         the output stream and the events of one tick
+                    -output:        Result type (did it succed or not)
+                    -description:   write each event (highlighted in a terminal, as an {"alert": ...} record in the
+                                    machine formats), append it to the event log (one JSON line) and start the hook
+                                    command with the event in PPSX_ALERT_* environment variables
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn report(&mut self, stream: &mut Stream, events: &[sp::AlertEvent]) -> Result<(), Box<dyn std::error::Error>> {
+        // reap the hooks that ended since the last tick
+        self.hooks.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+        for event in events {
+            let line = format!("===== ALERT {} PID {} ({}): {} [{}] =====", event.timestamp, event.pid, event.name, event.rule, event.value);
+            if stream.is_terminal() {
+                stream.text(&format!("\x1b[1;31m{}\x1b[0m\n", line))?;
+            } else {
+                stream.text(&format!("{}\n", line))?;
+            }
+            stream.record(&serde_json::json!({"alert": event}))?;
+
+            if let Some(log) = &mut self.log {
+                writeln!(log, "{}", serde_json::to_string(event)?)?;
+                log.flush()?;
+            }
+            if let Some(command) = &self.command {
+                let child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .env("PPSX_ALERT_TIME", &event.timestamp)
+                    .env("PPSX_ALERT_PID", event.pid.to_string())
+                    .env("PPSX_ALERT_NAME", &event.name)
+                    .env("PPSX_ALERT_RULE", &event.rule)
+                    .env("PPSX_ALERT_VALUE", &event.value)
+                    .spawn();
+                match child {
+                    Ok(child) => self.hooks.push(child),
+                    Err(e) => stream.text(&format!("===== Cannot run the alert command: {} =====\n", e))?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cpu: Option<f64>, rss_kb: usize, state: &str) -> sp::LiveSample {
+        sp::LiveSample {
+            pid: 42,
+            name: String::from("worker"),
+            state: state.to_string(),
+            cpu_percent: cpu,
+            memory_rss_kb: rss_kb,
+            memory_virtual_kb: 4 * rss_kb,
+            threads: 8,
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn thresholds() {
+        let rule = parse_rule("cpu>90").unwrap();
+        assert!(rule.hold.is_none());
+        assert_eq!(rule.check(&sample(Some(95.0), 0, "R")).as_deref(), Some("95.00%"));
+        assert_eq!(rule.check(&sample(Some(90.0), 0, "R")), None);
+        assert_eq!(rule.check(&sample(None, 0, "R")), None);
+
+        let rule = parse_rule("rss >= 2G").unwrap();
+        assert!(matches!(rule.condition, Condition::Threshold(Metric::Rss, Compare::AtLeast, limit) if limit == 2.0 * 1024.0 * 1024.0));
+        assert!(rule.check(&sample(None, 2 * 1024 * 1024, "S")).is_some());
+        assert!(parse_rule("virt<100M").unwrap().check(&sample(None, 1024, "S")).is_some());
+        assert!(parse_rule("threads<=8").unwrap().check(&sample(None, 0, "S")).is_some());
+    }
+
+    #[test]
+    fn hold_and_states() {
+        let rule = parse_rule("cpu>50 for 30s").unwrap();
+        assert_eq!(rule.hold, Some(Duration::from_secs(30)));
+        assert_eq!(rule.text, "cpu>50 for 30s");
+
+        let rule = parse_rule("state=dz").unwrap();
+        assert!(matches!(&rule.condition, Condition::State(states) if states == "DZ"));
+        assert_eq!(rule.check(&sample(None, 0, "Z")).as_deref(), Some("Z"));
+        assert_eq!(rule.check(&sample(None, 0, "S")), None);
+    }
+
+    #[test]
+    fn invalid_rules() {
+        assert!(parse_rule("cpu").is_err());
+        assert!(parse_rule("load>3").is_err());
+        assert!(parse_rule("cpu>high").is_err());
+        assert!(parse_rule("rss>2X").is_err());
+        assert!(parse_rule("state=").is_err());
+        assert!(parse_rule("cpu>90 for ever").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+/*
+----------------------------------------------------------------------------------------
+File used to read the cgroup v2 of a process: its path from /proc/{PID}/cgroup, resolved
+under the cgroup2 mount (/sys/fs/cgroup, or /sys/fs/cgroup/unified on hybrid systems), and
+the limits and usage files of the group. A file missing because its controller is not
+enabled for the group gives an empty field. With another procfs root (--proc-root), the
+mount is looked up in the mountinfo of its PID 1 and /sys is taken next to the root (ex:
+/host/sys for /host/proc), never from the host running ppsx.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static MOUNT: OnceLock<PathBuf> = OnceLock::new();
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function system_path:   -input:         an absolute path of the system whose processes are read (ex: "/sys/dev/block")
+                        -output:        the path as is with the live /proc, else the same path under the directory
+                                        holding the procfs root (ex: "/host/sys/dev/block" for /host/proc)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn system_path(path: &Path) -> PathBuf {
+    if procfs::is_live() {
+        return path.to_path_buf();
+    }
+    let relative = path.strip_prefix("/").unwrap_or(path);
+    procfs::root().parent().unwrap_or(Path::new("/")).join(relative)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function mount_point:   -input:         /
+                        -output:        the directory where the cgroup2 hierarchy is mounted, found in the mountinfo
+                                        of ppsx itself (of PID 1 with another procfs root); "/sys/fs/cgroup" if none
+                                        is listed, passed through system_path
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn mount_point() -> &'static Path {
+    MOUNT.get_or_init(|| {
+        let mountinfo = procfs::path(if procfs::is_live() { "self/mountinfo" } else { "1/mountinfo" });
+        // "ID PARENT MAJ:MIN ROOT MOUNT_POINT OPTIONS [TAGS...] - FSTYPE SOURCE OPTIONS"
+        let mount = fs::read_to_string(mountinfo).unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once(" - "))
+            .find(|(_, fs)| fs.starts_with("cgroup2 "))
+            .and_then(|(mount, _)| mount.split_whitespace().nth(4).map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("/sys/fs/cgroup"));
+        system_path(&mount)
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function cgroup_path:   -input:         a process id
+                        -output:        the cgroup v2 path of the process (the "0::" line of /proc/{PID}/cgroup, ex:
+                                        "/system.slice/nginx.service"); None if not readable or on a v1-only system
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn cgroup_path(pid: usize) -> Option<String> {
+    fs::read_to_string(procfs::pid_path(pid, "cgroup")).ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.trim().to_string())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_value:    -input:         the directory of a cgroup and the name of a file holding a single value
+                        -output:        the trimmed content of the file; None if missing
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_value(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_string())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function device_name:   -input:         a block device as "MAJOR:MINOR"
+                        -output:        its kernel name (ex: "sda") from /sys/dev/block (see system_path); the
+                                        numbers if unknown
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn device_name(device: &str) -> String {
+    fs::read_link(system_path(Path::new("/sys/dev/block")).join(device))
+        .ok()
+        .and_then(|target| target.file_name().and_then(|n| n.to_str()).map(|n| n.to_string()))
+        .unwrap_or_else(|| device.to_string())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_io_stat: -input:         the content of an io.stat file
+                        -output:        one CgroupIo per device ("8:0 rbytes=... wbytes=... rios=... wios=...")
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn parse_io_stat(content: &str) -> Vec<sp::CgroupIo> {
+    content.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mut io = sp::CgroupIo { device: device_name(device), read_bytes: 0, write_bytes: 0, read_ios: 0, write_ios: 0 };
+            for (key, value) in fields.filter_map(|f| f.split_once('=')) {
+                let value = value.parse().unwrap_or(0);
+                match key {
+                    "rbytes" => io.read_bytes = value,
+                    "wbytes" => io.write_bytes = value,
+                    "rios" => io.read_ios = value,
+                    "wios" => io.write_ios = value,
+                    _ => {}
+                }
+            }
+            Some(io)
+        })
+        .collect()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_cgroup:   -input:         a cgroup v2 path (as given by cgroup_path)
+                        -output:        a CgroupInfo with the limits and usage of the group; an empty field means that
+                                        the file is missing (controller not enabled, group removed...)
+                        -description:   read memory.current, memory.max, cpu.max, cpu.stat, pids.current, pids.max and
+                                        io.stat in the directory of the group under the cgroup2 mount
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_cgroup(path: &str) -> sp::CgroupInfo {
+    let dir = match path.trim_start_matches('/') {
+        "" => mount_point().to_path_buf(),
+        rel => mount_point().join(rel),
+    };
+    let number = |file: &str| read_value(&dir, file).and_then(|v| v.parse::<u64>().ok());
+
+    let cpu_max = read_value(&dir, "cpu.max");
+    // "QUOTA PERIOD" in microseconds, QUOTA being "max" without limit
+    let cpu_limit = cpu_max.as_ref().and_then(|max| {
+        let (quota, period) = max.split_once(' ')?;
+        Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+    });
+    let cpu_stat = read_value(&dir, "cpu.stat").unwrap_or_default();
+    let stat = |key: &str| cpu_stat.lines()
+        .find_map(|line| line.strip_prefix(key).and_then(|v| v.strip_prefix(' ')))
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    sp::CgroupInfo {
+        path: path.to_string(),
+        directory: dir.to_string_lossy().to_string(),
+        memory_current: number("memory.current"),
+        memory_max: read_value(&dir, "memory.max"),
+        cpu_max,
+        cpu_limit,
+        cpu_usage_usec: stat("usage_usec"),
+        nr_periods: stat("nr_periods"),
+        nr_throttled: stat("nr_throttled"),
+        throttled_usec: stat("throttled_usec"),
+        pids_current: number("pids.current"),
+        pids_max: read_value(&dir, "pids.max"),
+        io: read_value(&dir, "io.stat").map(|content| parse_io_stat(&content)).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,87 @@
+/*
+----------------------------------------------------------------------------------------
+File used to sum the processes of each cgroup (v2): number of processes and threads, rss
+and cpu usage, next to the limits and throttling counters of the group, to check whether
+a container or a service is being throttled.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::output::OutputOptions;
+use ppsx::cgroup::{cgroup_path, read_cgroup};
+use ppsx::struct_proc as sp;
+use ppsx::system::CPU_WINDOW;
+use ppsx::{processes, CpuSampler};
+use std::collections::BTreeMap;
+use std::thread;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render:    -input:         the usage of every cgroup
+                    -output:        an aligned table with one row per cgroup
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render(groups: &[sp::CgroupUsage]) -> String {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| String::from("-"));
+    let mut output = format!("===== Cgroups (cpu over {} ms) =====\n", CPU_WINDOW.as_millis());
+    output.push_str(&format!("{:>6}  {:>7}  {:>10}  {:>7}  {:>10}  {:>10}  {:>6}  {:>9}  {:>11}  {}\n",
+        "PROCS", "THREADS", "RSS(kB)", "CPU%", "MEM(kB)", "MAX(kB)", "CPUS", "THROTTLED", "THROT(ms)", "CGROUP"));
+    for g in groups {
+        let max = g.memory_max.as_ref().map(|m| m.parse::<u64>().map(|m| (m / 1024).to_string()).unwrap_or_else(|_| m.clone()));
+        output.push_str(&format!("{:>6}  {:>7}  {:>10}  {:>7.2}  {:>10}  {:>10}  {:>6}  {:>9}  {:>11}  {}\n",
+            g.processes, g.threads, g.rss_kb, g.cpu_percent,
+            or_dash(g.memory_current.map(|m| (m / 1024).to_string())), or_dash(max),
+            or_dash(g.cpu_limit.map(|c| format!("{:.2}", c))),
+            or_dash(g.nr_throttled.map(|n| n.to_string())), or_dash(g.throttled_usec.map(|t| (t / 1000).to_string())), g.path));
+    }
+    output
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function cgroups:   -input:         the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   sample the cpu time of every process twice, CPU_WINDOW apart, then sum the
+                                    processes of each cgroup v2 (sorted by path) and add the limits and counters of
+                                    the group, in the chosen format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn cgroups(out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut sampler = CpuSampler::new();
+    for process in processes()? {
+        let _ = sampler.sample(&process);
+    }
+    thread::sleep(CPU_WINDOW);
+
+    let mut groups: BTreeMap<String, sp::CgroupUsage> = BTreeMap::new();
+    for process in processes()? {
+        let (Ok(info), Some(path)) = (process.info(), cgroup_path(process.pid())) else { continue };
+        let cpu = sampler.sample(&process).ok().flatten().unwrap_or(0.0);
+        let group = groups.entry(path).or_insert_with_key(|path| {
+            let cg = read_cgroup(path);
+            sp::CgroupUsage {
+                path: cg.path,
+                processes: 0,
+                threads: 0,
+                rss_kb: 0,
+                cpu_percent: 0.0,
+                memory_current: cg.memory_current,
+                memory_max: cg.memory_max,
+                cpu_limit: cg.cpu_limit,
+                nr_throttled: cg.nr_throttled,
+                throttled_usec: cg.throttled_usec,
+            }
+        });
+        group.processes += 1;
+        group.threads += info.threads;
+        group.rss_kb += info.vm_rss;
+        group.cpu_percent += cpu;
+    }
+    if groups.is_empty() {
+        println!("===== No cgroup v2 found =====");
+        return Ok(());
+    }
+    let groups: Vec<sp::CgroupUsage> = groups.into_values()
+        .map(|g| sp::CgroupUsage { cpu_percent: (g.cpu_percent * 100.0).round() / 100.0, ..g })
+        .collect();
+    out.emit(&groups, || render(&groups))
+}
@@ -1,22 +1,60 @@
 use clap::Parser;
 use clap::Subcommand;
+use std::path::PathBuf;
 
+use crate::cgroups;
+use crate::events;
+use crate::events::EventsOptions;
+use crate::filter::ListOptions;
+use crate::list;
 use crate::live;
-use crate::proc;
+use crate::live::LiveOptions;
+use crate::maps;
+use crate::output::OutputOptions;
+use crate::pinfo;
+use crate::record;
+use crate::record::ReplayOptions;
+use crate::serve;
+use crate::serve::ServeOptions;
+use crate::signal;
+use crate::signal::SignalOptions;
+use crate::snapshot;
 use crate::stats;
+use crate::threads;
+use crate::top;
+use crate::tree;
+use crate::tune;
+use crate::tune::IoClass;
+use ppsx::procfs;
 
 #[derive(Subcommand)]
 enum ComList {
-    List {#[arg(long)]json: bool, #[arg(long)]file: bool},
-    Stats {#[arg(long)]json: bool, #[arg(long)]file: bool},
-    Live {pid: usize, #[arg(long)]json: bool},
-    Pinfo {pid: usize, #[arg(long)]json: bool, #[arg(long)]file: bool, #[arg(long)]all: bool},
+    List {#[command(flatten)]opts: ListOptions, #[command(flatten)]out: OutputOptions},
+    Stats {#[command(flatten)]out: OutputOptions},
+    Cgroups {#[command(flatten)]out: OutputOptions},
+    Live {#[command(flatten)]opts: LiveOptions, #[command(flatten)]out: OutputOptions},
+    Replay {#[command(flatten)]opts: ReplayOptions, #[command(flatten)]out: OutputOptions},
+    Pinfo {pid: usize, #[arg(long)]all: bool, #[command(flatten)]out: OutputOptions},
+    Maps {pid: usize, #[arg(long)]group: bool, #[command(flatten)]out: OutputOptions},
+    Threads {pid: usize, #[command(flatten)]out: OutputOptions},
+    Signal {#[command(flatten)]opts: SignalOptions},
+    Snapshot {#[command(flatten)]out: OutputOptions},
+    Diff {before: PathBuf, after: PathBuf, #[command(flatten)]out: OutputOptions},
+    Events {#[command(flatten)]opts: EventsOptions, #[command(flatten)]out: OutputOptions},
+    Serve {#[command(flatten)]opts: ServeOptions},
+    Top,
+    Renice {pid: usize, #[arg(allow_negative_numbers = true)]nice: i32, #[arg(long)]all_threads: bool},
+    Ionice {pid: usize, #[arg(long, value_enum)]class: Option<IoClass>, #[arg(long)]level: Option<u8>, #[arg(long)]all_threads: bool},
+    Affinity {pid: usize, cpus: Option<String>, #[arg(long)]all_threads: bool},
+    Tree {pid: Option<usize>, #[arg(long)]rss: bool, #[arg(long)]cpu: bool, #[arg(long)]threads: bool, #[command(flatten)]out: OutputOptions},
 } // Describes the command list and their arguments 
 
 #[derive(Parser)]
 pub struct Cli {
     #[command(subcommand)]
     command: ComList,
+    #[arg(long, global = true, default_value = "/proc")]
+    proc_root: PathBuf, // procfs mount to read from (host /proc bind-mounted in a container, captured tree...)
 }
 
 /*
@@ -27,10 +65,25 @@ Function handler:   -input:         a command line (allready parsed)
 ------------------------------------------------------------------------------------------------------------------------
 */
 pub fn handler(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    procfs::set_root(cli.proc_root);
     match cli.command {
-        ComList::List { json, file} => proc::list_proc(json, file),
-        ComList::Pinfo { pid, json, file, all} => proc::pinfo(pid, json, file, all),
-        ComList::Stats { json, file } => stats::statistics(json, file),
-        ComList::Live { pid, json } => live::start(pid, json),
+        ComList::List { opts, out } => list::list_proc(&opts, &out),
+        ComList::Pinfo { pid, all, out } => pinfo::pinfo(pid, all, &out),
+        ComList::Stats { out } => stats::statistics(&out),
+        ComList::Cgroups { out } => cgroups::cgroups(&out),
+        ComList::Live { opts, out } => live::start(opts, &out),
+        ComList::Replay { opts, out } => record::replay(opts, &out),
+        ComList::Maps { pid, group, out } => maps::maps(pid, group, &out),
+        ComList::Threads { pid, out } => threads::threads(pid, &out),
+        ComList::Signal { opts } => signal::send(opts),
+        ComList::Snapshot { out } => snapshot::snapshot(&out),
+        ComList::Diff { before, after, out } => snapshot::diff(&before, &after, &out),
+        ComList::Events { opts, out } => events::events(opts, &out),
+        ComList::Serve { opts } => serve::serve(opts),
+        ComList::Top => top::start(),
+        ComList::Renice { pid, nice, all_threads } => tune::renice(pid, nice, all_threads),
+        ComList::Ionice { pid, class, level, all_threads } => tune::ionice(pid, class, level, all_threads),
+        ComList::Affinity { pid, cpus, all_threads } => tune::affinity(pid, cpus, all_threads),
+        ComList::Tree { pid, rss, cpu, threads, out } => tree::tree(pid, (rss, cpu, threads), &out),
     }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+/*
+----------------------------------------------------------------------------------------
+File used to tell whether a process runs in a container: its namespaces (the inodes of
+the links of /proc/{PID}/ns) are compared with the ones of PID 1, and the runtime and the
+container ID are recognized in its cgroup paths (docker, containerd, podman, cri-o,
+systemd-nspawn, lxc, kubernetes pods).
+----------------------------------------------------------------------------------------
+*/
+
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::fs;
+
+pub const NAMESPACES: [&str; 7] = ["pid", "net", "mnt", "user", "uts", "ipc", "cgroup"]; // compared with PID 1
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_namespace: -input:        a process id and the name of a namespace (ex: "net")
+                         -output:       the inode of the namespace (the link /proc/{PID}/ns/net reads "net:[INODE]");
+                                        None if not readable (it needs the same privileges as ptrace)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_namespace(pid: usize, name: &str) -> Option<u64> {
+    let link = fs::read_link(procfs::pid_path(pid, &format!("ns/{}", name))).ok()?;
+    let link = link.to_str()?;
+    link.strip_prefix(name)?.strip_prefix(":[")?.strip_suffix(']')?.parse().ok()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_namespaces:   -input:         a process id
+                            -output:        one Namespace per entry of NAMESPACES, compared with the same namespace of
+                                            PID 1 when both are readable
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_namespaces(pid: usize) -> Vec<sp::Namespace> {
+    NAMESPACES.iter()
+        .map(|name| {
+            let inode = read_namespace(pid, name);
+            let differs = match (inode, read_namespace(1, name)) {
+                (Some(own), Some(init)) => Some(own != init),
+                _ => None,
+            };
+            sp::Namespace { name: name.to_string(), inode, differs }
+        })
+        .collect()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function is_id: -input:         a string
+                -output:        true if it looks like a container ID (64 hexadecimal digits)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn is_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_runtime: -input:         a cgroup path (ex: "/system.slice/docker-<ID>.scope")
+                        -output:        the runtime and the container ID (or machine name) it reveals; None if it is not
+                                        the cgroup of a container
+                        -description:   the last segments are checked first so that the innermost container wins
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_runtime(path: &str) -> Option<(String, String)> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    for (i, segment) in segments.iter().enumerate().rev() {
+        let parent = if i > 0 { segments[i - 1] } else { "" };
+        let scope = segment.strip_suffix(".scope").unwrap_or(segment);
+        let found = if let Some(id) = scope.strip_prefix("docker-") {
+            Some(("docker", id.to_string())) // systemd cgroup driver
+        } else if parent == "docker" && is_id(segment) {
+            Some(("docker", segment.to_string())) // cgroupfs driver
+        } else if let Some(id) = scope.strip_prefix("cri-containerd-") {
+            Some(("containerd", id.to_string()))
+        } else if let Some(id) = scope.strip_prefix("crio-") {
+            Some(("cri-o", id.to_string()))
+        } else if let Some(id) = scope.strip_prefix("libpod-") {
+            // "libpod-conmon-<ID>" is the monitor of the container, running on the host
+            (!id.starts_with("conmon-")).then(|| ("podman", id.to_string()))
+        } else if let Some(name) = scope.strip_prefix("machine-") {
+            // systemd escapes the '-' of the machine name as "\x2d"
+            (parent == "machine.slice").then(|| ("systemd-nspawn", name.replace("\\x2d", "-")))
+        } else if let Some(name) = segment.strip_prefix("lxc.payload.") {
+            Some(("lxc", name.to_string()))
+        } else if parent == "lxc" {
+            Some(("lxc", segment.to_string()))
+        } else if is_id(segment) && segments.iter().any(|s| s.starts_with("kubepods")) {
+            Some(("kubernetes", segment.to_string())) // pod container with the cgroupfs driver
+        } else {
+            None
+        };
+        if let Some((runtime, id)) = found
+            && !id.is_empty() {
+            return Some((runtime.to_string(), id));
+        }
+    }
+    None
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_container:    -input:         a process id
+                            -output:        a ContainerInfo: the runtime and ID found in the cgroup paths of
+                                            /proc/{PID}/cgroup (v2 and v1 lines) and the namespaces compared with PID 1
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_container(pid: usize) -> sp::ContainerInfo {
+    let found = fs::read_to_string(procfs::pid_path(pid, "cgroup")).unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(parse_runtime);
+    let namespaces = read_namespaces(pid);
+    let differing = namespaces.iter().filter(|ns| ns.differs == Some(true)).map(|ns| ns.name.clone()).collect();
+    let (runtime, id) = match found {
+        Some((runtime, id)) => (Some(runtime), Some(id)),
+        None => (None, None),
+    };
+    sp::ContainerInfo { runtime, id, namespaces, differing }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function is_container:  -input:         a ContainerInfo
+                        -output:        true if a runtime was recognized or if the process has its own pid namespace
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn is_container(info: &sp::ContainerInfo) -> bool {
+    info.runtime.is_some() || info.differing.iter().any(|ns| ns == "pid")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function container_label:   -input:         a ContainerInfo
+                            -output:        "RUNTIME:ID" (IDs shortened to 12 characters), "unknown" for a process in
+                                            its own pid namespace without known runtime; None outside containers
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn container_label(info: &sp::ContainerInfo) -> Option<String> {
+    if !is_container(info) {
+        return None;
+    }
+    match (&info.runtime, &info.id) {
+        (Some(runtime), Some(id)) if is_id(id) => Some(format!("{}:{}", runtime, &id[..12])),
+        (Some(runtime), Some(id)) => Some(format!("{}:{}", runtime, id)),
+        _ => Some(String::from("unknown")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "4f1c7e0d2b9a8c6e5d3f1a0b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d";
+
+    #[test]
+    fn docker() {
+        let expected = Some((String::from("docker"), ID.to_string()));
+        assert_eq!(parse_runtime(&format!("/system.slice/docker-{}.scope", ID)), expected);
+        assert_eq!(parse_runtime(&format!("/docker/{}", ID)), expected);
+    }
+
+    #[test]
+    fn containerd_crio_and_podman() {
+        assert_eq!(parse_runtime(&format!("/kubepods.slice/kubepods-burstable.slice/cri-containerd-{}.scope", ID)),
+            Some((String::from("containerd"), ID.to_string())));
+        assert_eq!(parse_runtime(&format!("/kubepods.slice/crio-{}.scope", ID)), Some((String::from("cri-o"), ID.to_string())));
+        assert_eq!(parse_runtime(&format!("/machine.slice/libpod-{}.scope/container", ID)), Some((String::from("podman"), ID.to_string())));
+        assert_eq!(parse_runtime(&format!("/machine.slice/libpod-conmon-{}.scope", ID)), None);
+        assert_eq!(parse_runtime(&format!("/kubepods/burstable/pod1234/{}", ID)), Some((String::from("kubernetes"), ID.to_string())));
+    }
+
+    #[test]
+    fn nspawn_and_lxc() {
+        assert_eq!(parse_runtime("/machine.slice/machine-my\\x2dbox.scope/payload"), Some((String::from("systemd-nspawn"), String::from("my-box"))));
+        assert_eq!(parse_runtime("/lxc.payload.web/init.scope"), Some((String::from("lxc"), String::from("web"))));
+        assert_eq!(parse_runtime("/lxc/web"), Some((String::from("lxc"), String::from("web"))));
+    }
+
+    #[test]
+    fn host_cgroups() {
+        assert_eq!(parse_runtime("/"), None);
+        assert_eq!(parse_runtime("/init.scope"), None);
+        assert_eq!(parse_runtime("/user.slice/user-1000.slice/session-1.scope"), None);
+        assert_eq!(parse_runtime("/system.slice/docker.service"), None);
+        assert_eq!(parse_runtime("/machine.slice/docker-.scope"), None);
+    }
+}
@@ -0,0 +1,68 @@
+/*
+----------------------------------------------------------------------------------------
+File used to describe the errors returned by the library API, so that a caller can tell
+a process that is gone from a process it is not allowed to inspect.
+----------------------------------------------------------------------------------------
+*/
+
+use std::fmt;
+use std::io;
+
+/// Error returned by the library API.
+#[derive(Debug)]
+pub enum Error {
+    /// No process has this pid (it may have exited while being read).
+    NotFound(usize),
+    /// The caller is not allowed to read this process.
+    PermissionDenied(usize),
+    /// A procfs file did not have the expected layout.
+    Malformed(String),
+    /// Any other I/O error (procfs root not readable...).
+    Io(io::Error),
+}
+
+/// Result type of the library API.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method from_io: -input:         the pid being read and the I/O error met
+                    -output:        the matching Error (a missing file means the process is gone)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub(crate) fn from_io(pid: usize, err: io::Error) -> Error {
+        match err.kind() {
+            io::ErrorKind::NotFound => Error::NotFound(pid),
+            io::ErrorKind::PermissionDenied => Error::PermissionDenied(pid),
+            io::ErrorKind::InvalidData => Error::Malformed(err.to_string()),
+            _ => Error::Io(err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(pid) => write!(f, "no process with PID {}", pid),
+            Error::PermissionDenied(pid) => write!(f, "permission denied on PID {}", pid),
+            Error::Malformed(message) => write!(f, "{}", message),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
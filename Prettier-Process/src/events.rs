@@ -0,0 +1,355 @@
+/*
+----------------------------------------------------------------------------------------
+File used to stream the process start and exit events (fork, exec, exit). They come from
+the netlink proc connector when ppsx is allowed to use it (root or CAP_NET_ADMIN, in the
+initial network namespace); otherwise successive scans of /proc are compared, which misses
+the processes living less than one scan interval and cannot know the exit codes.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::live::{catch_interrupt, parse_duration, wait, TIMESTAMP_FORMAT};
+use crate::output::{OutputOptions, Stream};
+use crate::signal::signal_name;
+use chrono::Local;
+use clap::Args;
+use ppsx::proc::{get_all_info, read_info};
+use ppsx::struct_proc as sp;
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::time::{Duration, Instant};
+
+const CN_VAL_PROC: u32 = 1; // id of the proc connector, with libc::CN_IDX_PROC
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_EVENT_FORK: u32 = 0x1;
+const PROC_EVENT_EXEC: u32 = 0x2;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+const NLMSG_HEADER: usize = 16; // size of struct nlmsghdr
+const CN_HEADER: usize = 20; // size of struct cn_msg
+const EVENT_DATA: usize = 16; // offset of event_data in struct proc_event (after what, cpu, timestamp_ns)
+
+#[derive(Args)]
+pub struct EventsOptions {
+    #[arg(long)]
+    pub scan: bool, // compare scans of /proc even if the proc connector is available
+    #[arg(long, default_value = "100ms", value_parser = parse_duration)]
+    pub interval: Duration, // time between two scans of /proc
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>, // stop after this time (ex: "30s", "5m")
+} // Options of the events command
+
+enum Raw {
+    Fork { ppid: usize, pid: usize },
+    Exec { pid: usize },
+    Exit { pid: usize, status: u32 },
+} // Process event as read from the proc connector
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function make_event:    -input:         the kind of event, what is known of the process and its wait status (exit only)
+                        -output:        the ProcessEvent, stamped with the current time
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn make_event(kind: &str, info: &sp::ProcessInfo, status: Option<u32>) -> sp::ProcessEvent {
+    let (exit_code, signal) = match status {
+        Some(status) if status & 0x7f == 0 => (Some(((status >> 8) & 0xff) as i32), None),
+        Some(status) => (None, Some(signal_name((status & 0x7f) as i32))),
+        None => (None, None),
+    };
+    sp::ProcessEvent {
+        timestamp: Local::now().format(TIMESTAMP_FORMAT).to_string(),
+        event: kind.to_string(),
+        pid: info.pid,
+        ppid: info.ppid,
+        name: info.name.clone(),
+        cmdline: info.cmdline.clone(),
+        exit_code,
+        signal,
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function print_event:   -input:         the output stream and an event
+                        -output:        Result type (did it succed or not)
+                        -description:   write one line per event in text format, one record in the machine formats
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn print_event(stream: &mut Stream, event: &sp::ProcessEvent) -> Result<(), Box<dyn std::error::Error>> {
+    if !stream.is_text() {
+        return stream.record(event);
+    }
+    let status = match (&event.exit_code, &event.signal) {
+        (Some(code), _) => format!("[exit {}] ", code),
+        (_, Some(signal)) => format!("[{}] ", signal),
+        _ => String::new(),
+    };
+    Ok(stream.text(&format!("{}  {:<5}  {:>7}  {:>7}  {:<16} {}{}\n", event.timestamp, event.event, event.pid, event.ppid, event.name,
+        status, event.cmdline))?)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function connect:   -input:         /
+                    -output:        a netlink socket subscribed to the proc connector; an Error if it is not allowed
+                                    (not privileged, not in the initial network namespace) or not available
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn connect() -> Result<OwnedFd, io::Error> {
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, libc::NETLINK_CONNECTOR) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+    let pid = std::process::id();
+
+    let mut address: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    address.nl_pid = pid;
+    address.nl_groups = libc::CN_IDX_PROC;
+    let bound = unsafe {
+        libc::bind(fd, &address as *const libc::sockaddr_nl as *const libc::sockaddr, mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t)
+    };
+    if bound < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // struct nlmsghdr + struct cn_msg + the PROC_CN_MCAST_LISTEN operation
+    let mut message: Vec<u8> = Vec::new();
+    message.extend(((NLMSG_HEADER + CN_HEADER + 4) as u32).to_ne_bytes());
+    message.extend((libc::NLMSG_DONE as u16).to_ne_bytes());
+    message.extend(0u16.to_ne_bytes()); // flags
+    message.extend(0u32.to_ne_bytes()); // seq
+    message.extend(pid.to_ne_bytes());
+    message.extend(libc::CN_IDX_PROC.to_ne_bytes());
+    message.extend(CN_VAL_PROC.to_ne_bytes());
+    message.extend(0u32.to_ne_bytes()); // seq
+    message.extend(0u32.to_ne_bytes()); // ack
+    message.extend(4u16.to_ne_bytes()); // len of the operation
+    message.extend(0u16.to_ne_bytes()); // flags
+    message.extend(PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    if unsafe { libc::send(fd, message.as_ptr() as *const libc::c_void, message.len(), 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // wake up regularly to notice Ctrl+C and the end of --duration
+    let timeout = libc::timeval { tv_sec: 0, tv_usec: 100_000 };
+    unsafe {
+        libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &timeout as *const libc::timeval as *const libc::c_void,
+            mem::size_of::<libc::timeval>() as libc::socklen_t)
+    };
+    Ok(socket)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_messages:    -input:         a datagram received from the proc connector
+                            -output:        the process events it holds (the events of threads are left out)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn parse_messages(buffer: &[u8]) -> Vec<Raw> {
+    let u32_at = |offset: usize| buffer.get(offset..offset + 4).and_then(|b| b.try_into().ok()).map(u32::from_ne_bytes);
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while let Some(len) = u32_at(offset) {
+        let len = len as usize;
+        if len < NLMSG_HEADER {
+            break;
+        }
+        let event = offset + NLMSG_HEADER + CN_HEADER;
+        let data = event + EVENT_DATA;
+        match u32_at(event) {
+            // fork: parent pid, parent tgid, child pid, child tgid; a thread has pid != tgid
+            Some(PROC_EVENT_FORK) => if let (Some(ppid), Some(pid), Some(tgid)) = (u32_at(data + 4), u32_at(data + 8), u32_at(data + 12))
+                && pid == tgid {
+                events.push(Raw::Fork { ppid: ppid as usize, pid: pid as usize });
+            },
+            // exec: pid, tgid
+            Some(PROC_EVENT_EXEC) => if let Some(tgid) = u32_at(data + 4) {
+                events.push(Raw::Exec { pid: tgid as usize });
+            },
+            // exit: pid, tgid, exit code (wait status), exit signal
+            Some(PROC_EVENT_EXIT) => if let (Some(pid), Some(tgid), Some(status)) = (u32_at(data), u32_at(data + 4), u32_at(data + 8))
+                && pid == tgid {
+                events.push(Raw::Exit { pid: pid as usize, status });
+            },
+            _ => {}
+        }
+        offset += (len + 3) & !3; // messages are aligned on 4 bytes
+    }
+    events
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function listen_netlink:    -input:         the proc connector socket, the output stream and the end of the session
+                            -output:        Result type (did it succed or not)
+                            -description:   read the events until the end; the processes are read from /proc on fork
+                                            and exec, and remembered so that their exit can still be described
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn listen_netlink(socket: OwnedFd, stream: &mut Stream, end: Option<Instant>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut known: HashMap<usize, sp::ProcessInfo> = get_all_info()?.into_iter().map(|p| (p.pid, p)).collect();
+    let mut buffer = [0u8; 8192];
+    while wait(Duration::ZERO) && end.is_none_or(|end| Instant::now() < end) {
+        let received = unsafe { libc::recv(socket.as_raw_fd(), buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), 0) };
+        if received < 0 {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EAGAIN) | Some(libc::EINTR) => continue,
+                Some(libc::ENOBUFS) => {
+                    stream.text("===== Some events were lost (too many at once) =====\n")?;
+                    continue;
+                }
+                _ => return Err(err.into()),
+            }
+        }
+        for raw in parse_messages(&buffer[..received as usize]) {
+            let event = match raw {
+                Raw::Fork { ppid, pid } => {
+                    // the child runs the program of its parent until it calls exec
+                    let info = read_info(pid).ok()
+                        .or_else(|| known.get(&ppid).map(|parent| sp::ProcessInfo { pid, ppid, ..parent.clone() }))
+                        .unwrap_or_else(|| unknown(pid, ppid));
+                    let event = make_event("fork", &info, None);
+                    known.insert(pid, info);
+                    event
+                }
+                Raw::Exec { pid } => {
+                    let ppid = known.get(&pid).map(|p| p.ppid).unwrap_or(0);
+                    let info = read_info(pid).unwrap_or_else(|_| unknown(pid, ppid));
+                    let event = make_event("exec", &info, None);
+                    known.insert(pid, info);
+                    event
+                }
+                Raw::Exit { pid, status } => {
+                    let info = known.remove(&pid).unwrap_or_else(|| unknown(pid, 0));
+                    make_event("exit", &info, Some(status))
+                }
+            };
+            print_event(stream, &event)?;
+        }
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function unknown:   -input:         a pid and its parent pid (0 if unknown)
+                    -output:        a ProcessInfo with only these fields, for a process gone before it could be read
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn unknown(pid: usize, ppid: usize) -> sp::ProcessInfo {
+    sp::ProcessInfo { pid, name: String::from("?"), state: String::new(), ppid, uid: 0, gid: 0, euid: 0, egid: 0, threads: 0, vm_size: 0, vm_rss: 0, cmdline: String::new() }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function listen_scan:   -input:         the output stream, the time between two scans and the end of the session
+                        -output:        Result type (did it succed or not)
+                        -description:   compare each scan of /proc (get_all_info) with the previous one: a new pid is
+                                        reported as a fork, a changed command line as an exec and a missing pid as an
+                                        exit (without exit code)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn listen_scan(stream: &mut Stream, interval: Duration, end: Option<Instant>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut known: HashMap<usize, sp::ProcessInfo> = get_all_info()?.into_iter().map(|p| (p.pid, p)).collect();
+    while wait(interval) && end.is_none_or(|end| Instant::now() < end) {
+        let mut current: HashMap<usize, sp::ProcessInfo> = get_all_info()?.into_iter().map(|p| (p.pid, p)).collect();
+        let mut events: Vec<sp::ProcessEvent> = Vec::new();
+        for (pid, info) in current.iter_mut() {
+            match known.remove(pid) {
+                None => events.push(make_event("fork", info, None)),
+                // zombies and kernel threads have an empty command line: keep the previous one
+                Some(before) if info.cmdline.is_empty() => info.cmdline = before.cmdline,
+                Some(before) if before.cmdline != info.cmdline => events.push(make_event("exec", info, None)),
+                Some(_) => {}
+            }
+        }
+        events.extend(known.values().map(|info| make_event("exit", info, None)));
+        events.sort_by_key(|e| e.pid);
+        for event in &events {
+            print_event(stream, event)?;
+        }
+        known = current;
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function events:    -input:         the events options and the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   stream the process events from the proc connector, or from /proc scans if it cannot
+                                    be used (or with --scan), until Ctrl+C or the end of --duration
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn events(opts: EventsOptions, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let end = opts.duration.map(|d| Instant::now() + d);
+    let socket = if opts.scan { None } else { Some(connect()) };
+    let mut stream = out.stream()?;
+    catch_interrupt();
+    match socket {
+        Some(Ok(socket)) => {
+            stream.text("===== Process events (proc connector) =====\n")?;
+            stream.text(&format!("{:<23}  {:<5}  {:>7}  {:>7}  {:<16} {}\n", "TIME", "EVENT", "PID", "PPID", "NAME", "COMMAND"))?;
+            listen_netlink(socket, &mut stream, end)
+        }
+        other => {
+            if let Some(Err(e)) = other {
+                stream.text(&format!("===== Proc connector unavailable ({}), scanning /proc every {} ms =====\n", e, opts.interval.as_millis()))?;
+            }
+            stream.text("===== Process events (/proc scan) =====\n")?;
+            stream.text(&format!("{:<23}  {:<5}  {:>7}  {:>7}  {:<16} {}\n", "TIME", "EVENT", "PID", "PPID", "NAME", "COMMAND"))?;
+            listen_scan(&mut stream, opts.interval, end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // netlink message as sent by the proc connector, the data given as u32 words
+    fn message(what: u32, data: &[u32]) -> Vec<u8> {
+        let len = NLMSG_HEADER + CN_HEADER + EVENT_DATA + 4 * data.len();
+        let mut buffer = Vec::with_capacity(len);
+        buffer.extend_from_slice(&(len as u32).to_ne_bytes());
+        buffer.resize(NLMSG_HEADER + CN_HEADER, 0);
+        buffer.extend_from_slice(&what.to_ne_bytes());
+        buffer.resize(NLMSG_HEADER + CN_HEADER + EVENT_DATA, 0); // cpu and timestamp
+        for word in data {
+            buffer.extend_from_slice(&word.to_ne_bytes());
+        }
+        buffer
+    }
+
+    #[test]
+    fn process_events() {
+        let mut buffer = message(PROC_EVENT_FORK, &[100, 100, 200, 200]);
+        buffer.extend(message(PROC_EVENT_EXEC, &[200, 200]));
+        buffer.extend(message(PROC_EVENT_EXIT, &[200, 200, 9, 17, 100, 100]));
+        let events = parse_messages(&buffer);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], Raw::Fork { ppid: 100, pid: 200 }));
+        assert!(matches!(events[1], Raw::Exec { pid: 200 }));
+        assert!(matches!(events[2], Raw::Exit { pid: 200, status: 9 }));
+    }
+
+    #[test]
+    fn thread_events_are_skipped() {
+        let mut buffer = message(PROC_EVENT_FORK, &[100, 100, 201, 100]);
+        buffer.extend(message(PROC_EVENT_EXIT, &[201, 100, 0, 0]));
+        buffer.extend(message(0x4, &[100, 100, 1000, 1000])); // uid change
+        assert!(parse_messages(&buffer).is_empty());
+    }
+
+    #[test]
+    fn truncated_messages() {
+        assert!(parse_messages(&[]).is_empty());
+        let buffer = message(PROC_EVENT_FORK, &[100, 100, 200, 200]);
+        assert!(parse_messages(&buffer[..buffer.len() - 8]).is_empty());
+        assert!(parse_messages(&[4, 0, 0, 0]).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+/*
+----------------------------------------------------------------------------------------
+File used to select, sort and shape the processes shown by the commands working on a set
+of processes (list...). The selectors are shared so that every command resolves them the
+same way.
+----------------------------------------------------------------------------------------
+*/
+
+use ppsx::container::{container_label, is_container, read_container};
+use ppsx::proc::read_uptime;
+use ppsx::stat::{lifetime_cpu_percent, read_stat};
+use ppsx::struct_proc as sp;
+use ppsx::users::{group_name, resolve_uid, user_name};
+use clap::{Args, ValueEnum};
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+#[derive(Args, Default)]
+pub struct Filters {
+    #[arg(long)]
+    pub user: Option<String>, // user name or numerical uid, matching the real or the effective uid
+    #[arg(long)]
+    pub state: Option<String>, // one or more state letters (ex: "RD")
+    #[arg(long)]
+    pub name: Option<String>, // regex matched against the process name
+    #[arg(long)]
+    pub min_rss: Option<usize>, // in kB
+    #[arg(long, num_args = 0..=1, default_missing_value = "", value_name = "RUNTIME|ID")]
+    pub container: Option<String>, // alone: any container; with a value: a runtime or the start of an ID
+} // Process selectors
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    Rss,
+    Cpu,
+    Pid,
+    Threads,
+    Start,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Column {
+    Pid,
+    Name,
+    State,
+    Ppid,
+    Uid,
+    Gid,
+    User,
+    Group,
+    Setid,
+    Threads,
+    #[value(name = "vm_size")]
+    VmSize,
+    #[value(name = "vm_rss")]
+    VmRss,
+    Cmdline,
+    Container,
+} // ProcessInfo fields that can be displayed (user and group are named from /etc, container read from /proc/{PID})
+
+#[derive(Args)]
+pub struct ListOptions {
+    #[command(flatten)]
+    pub filters: Filters,
+    #[arg(long, value_enum)]
+    pub sort: Option<SortKey>,
+    #[arg(long)]
+    pub reverse: bool,
+    #[arg(long)]
+    pub limit: Option<usize>,
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<Column>,
+} // Options of the list command
+
+impl Filters {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method is_empty:    -input:         /
+                        -output:        true if no selector is given
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn is_empty(&self) -> bool {
+        self.user.is_none() && self.state.is_none() && self.name.is_none() && self.min_rss.is_none() && self.container.is_none()
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method select:  -input:         a vector of ProcessInfo
+                    -output:        the processes matching every given selector; an Error if the regex or the user is
+                                    invalid
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn select(&self, procs: Vec<sp::ProcessInfo>) -> Result<Vec<sp::ProcessInfo>, Box<dyn std::error::Error>> {
+        let name = match &self.name {
+            Some(pattern) => Some(Regex::new(pattern)?),
+            None => None,
+        };
+        let uid = match &self.user {
+            Some(user) => Some(resolve_uid(user).ok_or_else(|| format!("unknown user: {}", user))?),
+            None => None,
+        };
+        Ok(procs.into_iter()
+            .filter(|p| uid.is_none_or(|uid| p.uid == uid || p.euid == uid))
+            .filter(|p| self.state.as_ref().is_none_or(|states| states.contains(p.state.as_str())))
+            .filter(|p| name.as_ref().is_none_or(|re| re.is_match(&p.name)))
+            .filter(|p| self.min_rss.is_none_or(|min| p.vm_rss >= min))
+            .filter(|p| self.container.as_ref().is_none_or(|wanted| {
+                let container = read_container(p.pid);
+                is_container(&container) && (wanted.is_empty()
+                    || container.runtime.as_deref() == Some(wanted.as_str())
+                    || container.id.as_ref().is_some_and(|id| id.starts_with(wanted.as_str())))
+            }))
+            .collect())
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function descendants:   -input:         the known processes and a root pid
+                        -output:        the root (if it exists) and all its descendants
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn descendants(procs: &[sp::ProcessInfo], root: usize) -> Vec<usize> {
+    if !procs.iter().any(|p| p.pid == root) {
+        return Vec::new();
+    }
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for p in procs {
+        if p.ppid != p.pid {
+            children.entry(p.ppid).or_default().push(p.pid);
+        }
+    }
+    let mut result = Vec::new();
+    let mut stack = vec![root];
+    while let Some(pid) = stack.pop() {
+        result.push(pid);
+        stack.extend(children.get(&pid).into_iter().flatten());
+    }
+    result
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function sort_procs:    -input:         a vector of ProcessInfo, the sort key and the reverse option
+                        -output:        /
+                        -description:   sort the processes in place; pid and start are sorted oldest first while rss,
+                                        cpu and threads are sorted biggest first, --reverse flips the order; cpu is
+                                        the usage averaged over the process life and start its start time, both read
+                                        from /proc/{PID}/stat
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn sort_procs(procs: &mut [sp::ProcessInfo], key: SortKey, reverse: bool) {
+    match key {
+        SortKey::Pid => procs.sort_by_key(|p| p.pid),
+        SortKey::Rss => procs.sort_by_key(|p| Reverse(p.vm_rss)),
+        SortKey::Threads => procs.sort_by_key(|p| Reverse(p.threads)),
+        SortKey::Start => procs.sort_by_cached_key(|p| read_stat(p.pid).map(|s| s.starttime).unwrap_or(0)),
+        SortKey::Cpu => {
+            let uptime = read_uptime();
+            // f64 is not Ord: sort on thousandths of percent
+            procs.sort_by_cached_key(|p| Reverse(read_stat(p.pid).map(|s| (lifetime_cpu_percent(&s, uptime) * 1000.0) as u64).unwrap_or(0)));
+        }
+    }
+    if reverse {
+        procs.reverse();
+    }
+}
+
+impl Column {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method header:  -input:         /
+                    -output:        the title of the column
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Name => "NAME",
+            Column::State => "STATE",
+            Column::Ppid => "PPID",
+            Column::Uid => "UID",
+            Column::Gid => "GID",
+            Column::User => "USER",
+            Column::Group => "GROUP",
+            Column::Setid => "SETID",
+            Column::Threads => "THREADS",
+            Column::VmSize => "VMSIZE(kB)",
+            Column::VmRss => "VMRSS(kB)",
+            Column::Cmdline => "COMMAND",
+            Column::Container => "CONTAINER",
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method key:     -input:         /
+                    -output:        the name of the ProcessInfo field shown by the column (used as JSON key)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn key(&self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Name => "name",
+            Column::State => "state",
+            Column::Ppid => "ppid",
+            Column::Uid => "uid",
+            Column::Gid => "gid",
+            Column::User => "user",
+            Column::Group => "group",
+            Column::Setid => "setid",
+            Column::Threads => "threads",
+            Column::VmSize => "vm_size",
+            Column::VmRss => "vm_rss",
+            Column::Cmdline => "cmdline",
+            Column::Container => "container",
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method value:   -input:         a ProcessInfo
+                    -output:        the value of the column for this process
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn value(&self, p: &sp::ProcessInfo) -> serde_json::Value {
+        match self {
+            Column::Pid => p.pid.into(),
+            Column::Name => p.name.clone().into(),
+            Column::State => p.state.clone().into(),
+            Column::Ppid => p.ppid.into(),
+            Column::Uid => p.uid.into(),
+            Column::Gid => p.gid.into(),
+            Column::User => user_name(p.uid).unwrap_or_else(|| p.uid.to_string()).into(),
+            Column::Group => group_name(p.gid).unwrap_or_else(|| p.gid.to_string()).into(),
+            Column::Setid => (p.euid != p.uid || p.egid != p.gid).into(),
+            Column::Threads => p.threads.into(),
+            Column::VmSize => p.vm_size.into(),
+            Column::VmRss => p.vm_rss.into(),
+            Column::Cmdline => p.cmdline.clone().into(),
+            Column::Container => container_label(&read_container(p.pid)).into(),
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method text:    -input:         a ProcessInfo
+                    -output:        the value of the column for this process as a table cell ("-" if empty)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn text(&self, p: &sp::ProcessInfo) -> String {
+        match self.value(p) {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Null => String::from("-"),
+            other => other.to_string(),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Readers for the Linux `/proc` filesystem, used by the `ppsx` command line tool.
+//!
+//! The entry points are [`processes`], which iterates over the running processes, the
+//! [`Process`] handle, whose [`Process::info`] and [`Process::full_info`] read what is known
+//! about a process, and [`CpuSampler`], which turns successive readings of the cpu time of
+//! processes into a usage. They return typed [`Error`]s.
+//!
+//! ```no_run
+//! use ppsx::{processes, Process};
+//!
+//! for process in processes()? {
+//!     if let Ok(info) = process.info() {
+//!         println!("{} {} {} kB", info.pid, info.name, info.vm_rss);
+//!     }
+//! }
+//! let init = Process::new(1)?.full_info()?;
+//! println!("init runs {} with {} open files", init.exe, init.fd_count);
+//! # Ok::<(), ppsx::Error>(())
+//! ```
+//!
+//! The lower level readers (`stat`, `mem`, `net`, `system`, `cgroup`, `container`, `users`...) are public as well and return
+//! `std::io::Error`. Every reader works under [`procfs::root`], `/proc` unless changed once
+//! with [`procfs::set_root`].
+
+pub mod cgroup;
+pub mod container;
+pub mod error;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod process;
+pub mod procfs;
+pub mod stat;
+pub mod struct_proc;
+pub mod system;
+pub mod users;
+
+pub use error::{Error, Result};
+pub use process::{processes, CpuSampler, Process, Processes};
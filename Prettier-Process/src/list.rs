@@ -0,0 +1,50 @@
+use crate::filter::{sort_procs, Column, ListOptions};
+use crate::output::{align, Format, OutputOptions};
+use ppsx::proc::get_all_info;
+use ppsx::struct_proc as sp;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function list_proc: -input:         the list options (filters, sort, limit and columns) and the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   call get_all_info, keep the processes matching the filters, sort and truncate them,
+                                    then display the result in the chosen format: "PID: x - name" lines by default, an
+                                    aligned table restricted to the given columns (pid and name if none) else
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn list_proc(opts: &ListOptions, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let proc = match get_all_info() {
+        Ok(p) => p,
+        Err(_) => {
+            println!("No process running?!");
+            let v: Vec<sp::ProcessInfo> = Vec::new();
+            v
+        }
+    };
+    let mut proc = opts.filters.select(proc)?;
+    if let Some(key) = opts.sort {
+        sort_procs(&mut proc, key, opts.reverse);
+    } else if opts.reverse {
+        proc.reverse();
+    }
+    if let Some(limit) = opts.limit {
+        proc.truncate(limit);
+    }
+
+    let columns = if opts.columns.is_empty() { vec![Column::Pid, Column::Name] } else { opts.columns.clone() };
+    if out.format() == Format::Table || (out.format() == Format::Text && !opts.columns.is_empty()) {
+        let header: Vec<String> = columns.iter().map(|c| c.header().to_string()).collect();
+        let rows: Vec<Vec<String>> = proc.iter().map(|p| columns.iter().map(|c| c.text(p)).collect()).collect();
+        return Ok(out.write(align(&header, &rows))?);
+    }
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = proc.iter()
+        .map(|p| columns.iter().map(|c| (c.key().to_string(), c.value(p))).collect())
+        .collect();
+    out.emit(&rows, || {
+        let mut output = String::new();
+        for p in &proc {
+            output.push_str(&format!("PID: {} - {}\n", p.pid, p.name));
+        }
+        output
+    })
+}
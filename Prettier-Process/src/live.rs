@@ -1,81 +1,420 @@
-use std::thread;
-use std::time::Duration;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
-use libc::{sysconf, _SC_CLK_TCK};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
 use chrono::Local;
-use crate::proc::{read_info};
+use clap::Args;
+use regex::Regex;
+use crate::alert::{parse_rule, Alerts, Rule};
+use crate::filter::descendants;
+use crate::output::{Format, OutputOptions, Stream};
+use crate::record::{parse_size, Recorder};
+use crate::threads::{read_samples, render};
+use ppsx::proc::get_all_info;
+use ppsx::{CpuSampler, Process};
+use ppsx::struct_proc as sp;
+
+#[derive(Args)]
+pub struct LiveOptions {
+    #[arg(required_unless_present_any = ["name", "tree"])]
+    pub pids: Vec<usize>,
+    #[arg(long)]
+    pub name: Option<String>, // regex matched against the process names at every tick
+    #[arg(long)]
+    pub tree: Option<usize>, // follow this pid and all its descendants
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    pub interval: Duration, // time between two samples (ex: "500ms", "2s")
+    #[arg(long)]
+    pub count: Option<usize>, // stop after this number of samples
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: Option<Duration>, // stop after this time (ex: "30s", "5m", "1h")
+    #[arg(long)]
+    pub record: Option<PathBuf>, // also append every sample to this file (NDJSON), see replay
+    #[arg(long, requires = "record", value_parser = parse_size)]
+    pub rotate_size: Option<u64>, // start a new file once the recording reaches this size (ex: "10M")
+    #[arg(long, requires = "record", value_parser = parse_duration)]
+    pub rotate_time: Option<Duration>, // start a new file once the recording is this old (ex: "1h")
+    #[arg(long = "alert", value_name = "RULE", value_parser = parse_rule)]
+    pub alerts: Vec<Rule>, // rules fired on the samples (ex: "cpu>90 for 30s", "rss>2G", "state=DZ")
+    #[arg(long, requires = "alerts")]
+    pub alert_log: Option<PathBuf>, // append every alert to this file (NDJSON)
+    #[arg(long, requires = "alerts")]
+    pub on_alert: Option<String>, // shell command run for every alert, with the event in PPSX_ALERT_* variables
+    #[arg(long)]
+    pub threads: bool, // also show the threads of every followed process
+} // Processes followed by the live command and how long to follow them
+
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f"; // local time of a sample, sortable as text
+pub const MAX_DURATION: Duration = Duration::from_secs(100 * 365 * 24 * 3600); // longest accepted duration (100 years)
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function catch_interrupt:   -input:         /
+                            -output:        /
+                            -description:   make Ctrl+C stop the waits (see wait and wait_line) instead of killing
+                                            ppsx, so that the summary can still be printed; the handler is installed
+                                            without SA_RESTART so that a blocking syscall fails with EINTR
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn catch_interrupt(){
+    unsafe{
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_interrupt as *const () as libc::sighandler_t;
+        action.sa_flags = 0;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_duration:    -input:         a duration as a number followed by an optional unit (ms, s, m or h;
+                                            seconds by default), ex: "250ms", "1.5", "5m"
+                            -output:        the matching Duration, capped to MAX_DURATION; an Error message if invalid
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_duration(text: &str)-> Result<Duration, String>{
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: f64 = value.parse().map_err(|_| format!("invalid duration: {}", text))?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        _ => return Err(format!("invalid duration unit: {} (expected ms, s, m or h)", unit)),
+    };
+    if seconds <= 0.0 {
+        return Err(format!("duration must be positive: {}", text));
+    }
+    let duration = Duration::try_from_secs_f64(seconds).map_err(|e| format!("invalid duration: {} ({})", text, e))?;
+    // Instant::now() + duration must not overflow
+    Ok(duration.min(MAX_DURATION))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function wait:  -input:         the time to wait
+                -output:        false if Ctrl+C was pressed in the meantime, true else
+                -description:   sleep by small steps so that Ctrl+C stops the monitor without waiting a whole interval
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn wait(time: Duration)-> bool{
+    let end = Instant::now() + time;
+    while !INTERRUPTED.load(Ordering::SeqCst){
+        let left = end.saturating_duration_since(Instant::now());
+        if left.is_zero(){
+            return true;
+        }
+        thread::sleep(left.min(Duration::from_millis(50)));
+    }
+    false
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function wait_line: -input:         /
+                    -output:        false if Ctrl+C was pressed before a line was entered, true else; an Error if
+                                    stdin cannot be read
+                    -description:   poll stdin by small steps before reading the line, since read_line itself retries
+                                    on EINTR and would ignore Ctrl+C
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn wait_line()-> Result<bool, io::Error>{
+    let mut stdin = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    while !INTERRUPTED.load(Ordering::SeqCst){
+        if unsafe{ libc::poll(&mut stdin, 1, 50) } > 0 {
+            io::stdin().read_line(&mut String::new())?;
+            return Ok(!INTERRUPTED.load(Ordering::SeqCst));
+        }
+    }
+    Ok(false)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function start_time:    -input:         a process id
+                        -output:        its start time after boot in clock ticks; None if it does not exist
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn start_time(pid: usize)-> Option<u64>{
+    Process::new(pid).ok()?.stat().ok().map(|stat| stat.starttime)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve_targets:    -input:         the live options, the compiled --name regex and the explicit pids with the
+                                             start time they had when live started
+                             -output:        the sorted pids to follow at this tick
+                             -description:   an explicit pid is dropped for good once its process is gone or has been
+                                             replaced by another one with the same pid (other start time), while
+                                             --name and --tree are resolved again from a fresh scan at each tick so
+                                             that new matching processes and new descendants are picked up
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn resolve_targets(opts: &LiveOptions, name: Option<&Regex>, explicit: &mut BTreeMap<usize, u64>)-> Vec<usize>{
+    explicit.retain(|pid, started| start_time(*pid) == Some(*started));
+    let mut targets: BTreeSet<usize> = explicit.keys().copied().collect();
+    if name.is_some() || opts.tree.is_some() {
+        let procs = get_all_info().unwrap_or_default();
+        if let Some(re) = name {
+            targets.extend(procs.iter().filter(|p| re.is_match(&p.name)).map(|p| p.pid));
+        }
+        if let Some(root) = opts.tree {
+            targets.extend(descendants(&procs, root));
+        }
+    }
+    targets.into_iter().collect()
+}
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function get_cpu_info:  -input:         a pid
-                        -output:        the cpu usage for the given process
-                        -description:   read cpu information in "/proc/{}/stat" and return it
+Function print_samples: -input:         the output stream, the samples of one tick and the samples of their threads
+                                        (empty without --threads)
+                        -output:        Result type (did it succed or not)
+                        -description:   write one record per process then per thread, or in text format an aligned
+                                        table with one row per process followed by the table of the threads
 ------------------------------------------------------------------------------------------------------------------------
 */
-fn get_cpu_info(pid: usize)-> Result<u64, io::Error>{
-    let content = fs::read_to_string(format!("/proc/{}/stat", pid))?;
-    let split: Vec<&str> = content.split_whitespace().collect();
-    let user_t = split.get(13).unwrap_or(&"0").parse::<u64>().unwrap_or(0);
-    let sys_t = split.get(14).unwrap_or(&"0").parse::<u64>().unwrap_or(0);
-    let total = user_t + sys_t;
-
-    Ok(total)
+pub fn print_samples(stream: &mut Stream, samples: &[sp::LiveSample], threads: &[sp::ThreadSample])-> Result<(), Box<dyn std::error::Error>>{
+    if !stream.is_text(){
+        for sample in samples{
+            stream.record(sample)?;
+        }
+        for thread in threads{
+            stream.record(thread)?;
+        }
+        return Ok(());
+    }
+    let width = samples.iter().map(|s| s.name.len()).max().unwrap_or(0).max(4);
+    // the tick time without its milliseconds
+    let time = samples.first().map(|s| s.timestamp.split('.').next().unwrap_or_default()).unwrap_or_default();
+    let mut output = format!("--------- {} ---------\n", time);
+    output.push_str(&format!("{:>7}  {:<width$}  {:^5}  {:>7}  {:>10}  {:>10}  {:>7}\n", "PID", "NAME", "STATE", "CPU%", "RSS(kB)", "VIRT(kB)", "THREADS", width = width));
+    for sample in samples{
+        let cpu = sample.cpu_percent.map(|c| format!("{:.2}", c)).unwrap_or_else(|| String::from("-"));
+        output.push_str(&format!("{:>7}  {:<width$}  {:^5}  {:>7}  {:>10}  {:>10}  {:>7}\n", sample.pid, sample.name, sample.state, cpu,
+            sample.memory_rss_kb, sample.memory_virtual_kb, sample.threads, width = width));
+    }
+    if !threads.is_empty(){
+        output.push('\n');
+        output.push_str(&render(threads));
+    }
+    output.push_str("\nPress Ctrl+C to stop\n\n");
+    Ok(stream.text(&output)?)
 }
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function start: -input:         a pid and option as bolean (json)
+Function add_to_summary:    -input:         the summaries of every process and the samples of one tick
+                            -output:        /
+                            -description:   count the samples and update the cpu min/max/average and the peak rss of
+                                            each process (the first sample of a process has no cpu value)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn add_to_summary(summary: &mut BTreeMap<usize, sp::LiveSummary>, samples: &[sp::LiveSample]){
+    for sample in samples{
+        let entry = summary.entry(sample.pid).or_insert_with(|| sp::LiveSummary {
+            pid: sample.pid,
+            name: sample.name.clone(),
+            samples: 0,
+            cpu_samples: 0,
+            cpu_min: None,
+            cpu_max: None,
+            cpu_avg: None,
+            peak_rss_kb: 0,
+        });
+        if let Some(cpu) = sample.cpu_percent{
+            let measured = entry.cpu_samples as f64;
+            entry.cpu_min = Some(entry.cpu_min.map_or(cpu, |m| m.min(cpu)));
+            entry.cpu_max = Some(entry.cpu_max.map_or(cpu, |m| m.max(cpu)));
+            entry.cpu_avg = Some(entry.cpu_avg.map_or(cpu, |a| (a * measured + cpu) / (measured + 1.0)));
+            entry.cpu_samples += 1;
+        }
+        entry.samples += 1;
+        entry.peak_rss_kb = entry.peak_rss_kb.max(sample.memory_rss_kb);
+    }
+}
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function print_summary: -input:         the output stream and the summaries of every followed process
+                        -output:        Result type (did it succed or not)
+                        -description:   write the summary of the session as a table, as one record per process in csv,
+                                        or as a single record ({"summary": [...]}) so that scripts can parse the last
+                                        line of a json output
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn print_summary(stream: &mut Stream, summary: BTreeMap<usize, sp::LiveSummary>)-> Result<(), Box<dyn std::error::Error>>{
+    let mut summary: Vec<sp::LiveSummary> = summary.into_values().collect();
+    for s in summary.iter_mut(){
+        s.cpu_avg = s.cpu_avg.map(|a| (a * 100.0).round() / 100.0);
+    }
+    match stream.format(){
+        Format::Text | Format::Table => {}
+        Format::Csv => {
+            for s in &summary{
+                stream.record(s)?;
+            }
+            return Ok(());
+        }
+        _ => return stream.record(&serde_json::json!({"summary": summary})),
+    }
+    let width = summary.iter().map(|s| s.name.len()).max().unwrap_or(0).max(4);
+    let cpu = |c: Option<f64>| c.map(|c| format!("{:.2}", c)).unwrap_or_else(|| String::from("-"));
+    let mut output = String::from("===== Summary =====\n");
+    output.push_str(&format!("{:>7}  {:<width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>12}\n", "PID", "NAME", "SAMPLES", "CPU MIN", "CPU MAX", "CPU AVG", "PEAK RSS(kB)", width = width));
+    for s in &summary{
+        output.push_str(&format!("{:>7}  {:<width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>12}\n", s.pid, s.name, s.samples, cpu(s.cpu_min), cpu(s.cpu_max),
+            cpu(s.cpu_avg), s.peak_rss_kb, width = width));
+    }
+    Ok(stream.text(&output)?)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function start: -input:         the live options (targets, interval, count, duration) and the output options
                 -output:        Result type (did it succed or not)
-                -description:   start the live monitoring of the given process by reading its information (read_info)
-                                every second and displaying the result depending on the option
+                -description:   start the live monitoring of the selected processes by reading their information
+                                (Process::info) at every interval and writing one row (or record) per process;
+                                the monitor stops after --count samples, after --duration, on Ctrl+C or once every
+                                followed process is gone (unless --name keeps waiting for new matches), then prints
+                                a summary of the session; with --record every sample is also appended to a file
+                                and with --alert the rules are checked at every tick; --threads adds the threads
+                                of every process after its samples
 ------------------------------------------------------------------------------------------------------------------------
 */
-pub fn start(pid: usize, json: bool)-> Result<(), Box<dyn std::error::Error>>{
-    let mut prev_cpu = get_cpu_info(pid)?;
-    let mut time = std::time::Instant::now();
+pub fn start(opts: LiveOptions, out: &OutputOptions)-> Result<(), Box<dyn std::error::Error>>{
+    let name = match &opts.name{
+        Some(pattern) => Some(Regex::new(pattern)?),
+        None => None,
+    };
 
-    let _info = match read_info(pid){
-        Ok(p) => p,
-        Err(_) => {println!("===== PID not found ====="); return Ok(());}
+    let mut sampler = CpuSampler::new();
+    let mut thread_sampler = CpuSampler::new(); // tids share the numbers of pids, so they get their own sampler
+    let mut explicit: BTreeMap<usize, u64> = opts.pids.iter().filter_map(|&pid| start_time(pid).map(|t| (pid, t))).collect();
+    for pid in resolve_targets(&opts, name.as_ref(), &mut explicit){
+        if let Ok(process) = Process::new(pid){
+            let _ = sampler.sample(&process);
+            if opts.threads{
+                let _ = read_samples(&process, &mut thread_sampler, "");
+            }
+        }
+    }
+    if sampler.pids().next().is_none() && name.is_none(){
+        println!("===== PID not found =====");
+        return Ok(());
+    }
+    let mut recorder = match &opts.record{
+        Some(path) => Some(Recorder::open(path.clone(), opts.rotate_size, opts.rotate_time)?),
+        None => None,
     };
-    println!("===== Live Monitor =====");
-    loop{
-        thread::sleep(Duration::from_secs(1));
+    let mut alerts = None;
+    if !opts.alerts.is_empty(){
+        alerts = Some(Alerts::new(opts.alerts.clone(), opts.alert_log.as_deref(), opts.on_alert.clone())?);
+    }
+    let mut stream = out.stream()?;
+    stream.text("===== Live Monitor =====\n")?;
+    catch_interrupt();
 
-        let info = match read_info(pid){
-            Ok(p) => p,
-            Err(_) => {println!("===== Process terminated ====="); return Ok(());}
-        };
-        let cpu = match get_cpu_info(pid){
-            Ok(c) => c,
-            Err(_) => {println!("===== Process terminated ====="); return Ok(());}
+    let started = Instant::now();
+    let mut ticks = 0;
+    let mut summary: BTreeMap<usize, sp::LiveSummary> = BTreeMap::new();
+    loop{
+        if opts.count.is_some_and(|count| ticks >= count) || opts.duration.is_some_and(|d| started.elapsed() >= d){
+            break;
+        }
+        let interval = match opts.duration{
+            Some(d) => opts.interval.min(d.saturating_sub(started.elapsed())),
+            None => opts.interval,
         };
-        
-        let delta_t = (std::time::Instant::now() - time).as_secs_f64();
-        let tick = unsafe{sysconf(_SC_CLK_TCK)};
-        let usage = ((cpu-prev_cpu) as f64 /tick as f64/delta_t)*100.0;
-
-        if json{
-            let output = serde_json::json!({
-                "pid": pid,
-                "name": info.name,
-                "state": info.state,
-                "cpu_percent": format!("{:.2}", usage),
-                "memory_rss_kb": info.vm_rss,
-                "memory_virtual_kb": info.vm_size,
-                "timestamp": Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+        if !wait(interval){
+            break;
+        }
+        ticks += 1;
+
+        let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+        let mut samples = Vec::new();
+        for pid in resolve_targets(&opts, name.as_ref(), &mut explicit){
+            let Ok(process) = Process::new(pid) else { continue };
+            let (Ok(info), Ok(usage)) = (process.info(), sampler.sample(&process)) else { continue };
+            samples.push(sp::LiveSample {
+                pid,
+                name: info.name,
+                state: info.state,
+                cpu_percent: usage.map(|u| (u * 100.0).round() / 100.0),
+                memory_rss_kb: info.vm_rss,
+                memory_virtual_kb: info.vm_size,
+                threads: info.threads,
+                timestamp: timestamp.clone(),
             });
-            println!("{}", serde_json::to_string_pretty(&output)?); 
         }
-        else{
-            println!("---- Process Status ----\n PID: {} | Name: {} | State: {}", pid, info.name, info.state);
-            println!("--- Ressources Usage ---\n CPU Usage: {:.2}% | Memory RSS:  {} kB ({} MB) | Memory Virt: {} kB ({} MB)", usage, info.vm_rss, info.vm_rss / 1024, info.vm_size, info.vm_size / 1024);
-            println!("--------- Time ---------\n {}", Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
-            println!("\nPress Ctrl+C to stop\n");
+        let mut gone: Vec<usize> = sampler.pids().filter(|pid| !samples.iter().any(|s| s.pid == *pid)).collect();
+        gone.sort();
+        for pid in &gone{
+            stream.text(&format!("===== Process {} terminated =====\n", pid))?;
         }
+        sampler.retain(|pid| !gone.contains(&pid));
+        if let Some(alerts) = &mut alerts{
+            alerts.retain(|pid| !gone.contains(&pid));
+        }
+
+        if samples.is_empty(){
+            if name.is_none(){
+                stream.text("===== Process terminated =====\n")?;
+                break;
+            }
+            continue;
+        }
+        let mut threads = Vec::new();
+        if opts.threads{
+            for sample in &samples{
+                if let Ok(process) = Process::new(sample.pid)
+                    && let Ok(sample_threads) = read_samples(&process, &mut thread_sampler, &timestamp){
+                    threads.extend(sample_threads);
+                }
+            }
+            thread_sampler.retain(|tid| threads.iter().any(|t| t.tid == tid));
+        }
+        add_to_summary(&mut summary, &samples);
+        if let Some(recorder) = &mut recorder{
+            recorder.write(&samples)?;
+        }
+        print_samples(&mut stream, &samples, &threads)?;
+        if let Some(alerts) = &mut alerts{
+            let events = alerts.check(&samples, Instant::now());
+            alerts.report(&mut stream, &events)?;
+        }
+    }
+    print_summary(&mut stream, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        prev_cpu = cpu;
-        time = std::time::Instant::now();
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("999999999h"), Ok(MAX_DURATION));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn invalid_durations() {
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("fast").is_err());
+        assert!(parse_duration("99999999999999999999999h").is_err());
+    }
+}
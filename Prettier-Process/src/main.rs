@@ -1,10 +1,24 @@
 use clap::Parser;
 
+mod alert;
+mod cgroups;
 mod cli;
+mod events;
+mod filter;
+mod list;
 mod live;
-mod proc;
+mod maps;
+mod output;
+mod pinfo;
+mod record;
+mod serve;
+mod signal;
+mod snapshot;
 mod stats;
-mod struct_proc;
+mod threads;
+mod top;
+mod tree;
+mod tune;
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,46 @@
+use crate::output::OutputOptions;
+use ppsx::mem::{group_maps, read_maps};
+use ppsx::struct_proc as sp;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function maps:  -input:         pid of the wanted process, option as boolean (group) and the output options
+                -output:        Result type (did it succed or not)
+                -description:   read the memory mappings of the process and display them (one row per mapping, or
+                                one row per backing file with --group) followed by the totals, in the chosen
+                                format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn maps(pid: usize, group: bool, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let maps = match read_maps(pid) {
+        Ok(m) => m,
+        Err(_) => {
+            println!("===== PID id not reconized =====");
+            return Ok(());
+        }
+    };
+    let groups = group_maps(&maps);
+
+    let text = || {
+        let mut output = String::new();
+        let header = format!("{:>10} {:>10} {:>10} {:>10} {:>10} {:>8}", "SIZE(kB)", "RSS", "PSS", "SWAP", "ANON", "THP");
+        if group {
+            output.push_str(&format!("{:>5} {}  PATH\n", "MAPS", header));
+            for g in &groups {
+                output.push_str(&format!("{:>5} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8}  {}\n",
+                    g.mappings, g.size_kb, g.rss, g.pss, g.swap, g.anonymous, g.thp, g.path));
+            }
+        } else {
+            output.push_str(&format!("{:<33} {:<5} {}  PATH\n", "ADDRESS", "PERMS", header));
+            for m in &maps {
+                output.push_str(&format!("{:<33} {:<5} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8}  {}\n",
+                    format!("{}-{}", m.start, m.end), m.perms, m.size_kb, m.rss, m.pss, m.swap, m.anonymous, m.thp, m.path));
+            }
+        }
+        let sum = |f: fn(&sp::MapGroup) -> u64| groups.iter().map(f).sum::<u64>();
+        output.push_str(&format!("\nTotal: {} mappings | Size: {} kB | RSS: {} kB | PSS: {} kB | Swap: {} kB | Anonymous: {} kB | THP: {} kB\n",
+            maps.len(), sum(|g| g.size_kb), sum(|g| g.rss), sum(|g| g.pss), sum(|g| g.swap), sum(|g| g.anonymous), sum(|g| g.thp)));
+        output
+    };
+    if group { out.emit(&groups, text) } else { out.emit(&maps, text) }
+}
@@ -0,0 +1,175 @@
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_kb_fields:    -input:         the content of a "Key:   value kB" file (meminfo, smaps_rollup...)
+                            -output:        a map from every key to its value (in kB for the sizes)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_kb_fields(content: &str) -> HashMap<String, u64> {
+    content.lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.split_whitespace().next()?.parse().ok()?;
+            Some((key.trim().to_string(), value))
+        })
+        .collect()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_smaps_rollup: -input:         a process id
+                            -output:        the memory accounting of the process; None if /proc/{PID}/smaps_rollup
+                                            is not readable (kernel thread, missing permission, kernel < 4.14)
+                            -description:   the PSS shares every page between the processes mapping it, so the sum
+                                            of the PSS of all processes is the real memory used; the USS (private
+                                            pages) is what would be freed if the process exited
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_smaps_rollup(pid: usize) -> Option<sp::SmapsRollup> {
+    let content = fs::read_to_string(procfs::pid_path(pid, "smaps_rollup")).ok()?;
+    let fields = read_kb_fields(&content);
+    if !fields.contains_key("Pss") {
+        return None; // Empty for kernel threads
+    }
+    let get = |key: &str| fields.get(key).copied().unwrap_or(0);
+    Some(sp::SmapsRollup {
+        rss: get("Rss"),
+        pss: get("Pss"),
+        uss: get("Private_Clean") + get("Private_Dirty"),
+        shared_clean: get("Shared_Clean"),
+        shared_dirty: get("Shared_Dirty"),
+        private_clean: get("Private_Clean"),
+        private_dirty: get("Private_Dirty"),
+        swap: get("Swap"),
+        swap_pss: get("SwapPss"),
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_meminfo:  -input:         /
+                        -output:        the system memory figures of /proc/meminfo (0 for the missing ones)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_meminfo() -> sp::MemInfo {
+    let fields = read_kb_fields(&fs::read_to_string(procfs::path("meminfo")).unwrap_or_default());
+    let get = |key: &str| fields.get(key).copied().unwrap_or(0);
+    sp::MemInfo {
+        mem_total: get("MemTotal"),
+        mem_free: get("MemFree"),
+        mem_available: get("MemAvailable"),
+        buffers: get("Buffers"),
+        cached: get("Cached"),
+        shmem: get("Shmem"),
+        swap_total: get("SwapTotal"),
+        swap_free: get("SwapFree"),
+        swap_cached: get("SwapCached"),
+        anon_hugepages: get("AnonHugePages"),
+        hugepages_total: get("HugePages_Total"),
+        hugepages_free: get("HugePages_Free"),
+        hugepage_size: get("Hugepagesize"),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_header:  -input:         a line of /proc/{PID}/maps (or a header line of /proc/{PID}/smaps)
+                        -output:        a MapEntry with the address range, permissions, offset and backing file of the
+                                        mapping (the sizes still at 0); None if the line is not a mapping header
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn parse_header(line: &str) -> Option<sp::MapEntry> {
+    let mut parts = line.split_whitespace();
+    let (start, end) = parts.next()?.split_once('-')?;
+    let size_kb = (u64::from_str_radix(end, 16).ok()?.saturating_sub(u64::from_str_radix(start, 16).ok()?)) / 1024;
+    let perms = parts.next()?.to_string();
+    let offset = parts.next()?.to_string();
+    let _dev = parts.next()?;
+    let _inode = parts.next()?;
+    let path = parts.collect::<Vec<&str>>().join(" ");
+    Some(sp::MapEntry {
+        start: start.to_string(),
+        end: end.to_string(),
+        size_kb,
+        perms,
+        offset,
+        path: if path.is_empty() { String::from("[anon]") } else { path },
+        rss: 0,
+        pss: 0,
+        swap: 0,
+        anonymous: 0,
+        thp: 0,
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_maps: -input:         a process id
+                    -output:        every mapping of the process; an Error if the process does not exist
+                    -description:   parse /proc/{PID}/smaps (a header line per mapping followed by its "Key: value kB"
+                                    lines); if it is not readable, fall back on /proc/{PID}/maps without the sizes
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_maps(pid: usize) -> Result<Vec<sp::MapEntry>, io::Error> {
+    let content = match fs::read_to_string(procfs::pid_path(pid, "smaps")) {
+        Ok(content) => content,
+        Err(_) => fs::read_to_string(procfs::pid_path(pid, "maps"))?,
+    };
+    let mut maps: Vec<sp::MapEntry> = Vec::new();
+    for line in content.lines() {
+        if let Some(entry) = parse_header(line) {
+            maps.push(entry);
+            continue;
+        }
+        let Some(entry) = maps.last_mut() else { continue };
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value: u64 = value.split_whitespace().next().and_then(|v| v.parse().ok()).unwrap_or(0);
+        match key {
+            "Rss" => { entry.rss = value; }
+            "Pss" => { entry.pss = value; }
+            "Swap" => { entry.swap = value; }
+            "Anonymous" => { entry.anonymous = value; }
+            "AnonHugePages" => { entry.thp = value; }
+            _ => {}
+        }
+    }
+    Ok(maps)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function group_maps:    -input:         the mappings of a process
+                        -output:        one MapGroup per backing file (or [heap], [stack], [anon]...) summing its
+                                        mappings, biggest RSS first
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn group_maps(maps: &[sp::MapEntry]) -> Vec<sp::MapGroup> {
+    let mut groups: HashMap<&str, sp::MapGroup> = HashMap::new();
+    for m in maps {
+        let group = groups.entry(m.path.as_str()).or_insert_with(|| sp::MapGroup {
+            path: m.path.clone(),
+            mappings: 0,
+            size_kb: 0,
+            rss: 0,
+            pss: 0,
+            swap: 0,
+            anonymous: 0,
+            thp: 0,
+        });
+        group.mappings += 1;
+        group.size_kb += m.size_kb;
+        group.rss += m.rss;
+        group.pss += m.pss;
+        group.swap += m.swap;
+        group.anonymous += m.anonymous;
+        group.thp += m.thp;
+    }
+    let mut groups: Vec<sp::MapGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.rss.cmp(&a.rss).then(a.path.cmp(&b.path)));
+    groups
+}
@@ -0,0 +1,197 @@
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::collections::HashSet;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function socket_inodes: -input:         a process id
+                        -output:        the set of socket inodes opened by this process (empty if not readable)
+                        -description:   read every link of "/proc/{PID}/fd" and keep the inode of the ones looking
+                                        like "socket:[inode]"
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn socket_inodes(pid: usize) -> HashSet<u64> {
+    fs::read_dir(procfs::pid_path(pid, "fd"))
+        .map(|entries| {
+            entries.filter_map(|e| e.ok())
+                .filter_map(|e| fs::read_link(e.path()).ok())
+                .filter_map(|p| {
+                    p.to_str()
+                        .and_then(|s| s.strip_prefix("socket:["))
+                        .and_then(|s| s.strip_suffix(']'))
+                        .and_then(|s| s.parse().ok())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function decode_address:    -input:         an address as written in /proc/net/{tcp,udp}[6] ("ADDR:PORT" in hex)
+                            -output:        the readable address and the port (None if malformed)
+                            -description:   the address is stored as 32 bits words in host byte order (little endian),
+                                            one word for IPv4 and four for IPv6; the port is big endian
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn decode_address(raw: &str) -> Option<(String, u16)> {
+    let (addr, port) = raw.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..addr.len()).step_by(8) {
+        let word = u32::from_str_radix(addr.get(i..i + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    let addr = match bytes.len() {
+        4 => Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string(),
+        16 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&bytes);
+            Ipv6Addr::from(octets).to_string()
+        }
+        _ => return None,
+    };
+    Some((addr, port))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function tcp_state: -input:         the hexadecimal state code of a socket
+                    -output:        the name of the state (as in include/net/tcp_states.h)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn tcp_state(code: &str) -> String {
+    match u8::from_str_radix(code, 16).unwrap_or(0) {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        0x0C => "NEW_SYN_RECV",
+        _ => "UNKNOWN",
+    }.to_string()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_inet: -input:         a process id, the protocol table to read ("tcp", "tcp6", "udp" or "udp6") and the
+                                    socket inodes owned by the process
+                    -output:        a vector of SocketEntry (empty if the table is not readable)
+                    -description:   parse "/proc/{PID}/net/{protocol}" (the table of the process network namespace)
+                                    and keep only the lines whose inode belongs to the process
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_inet(pid: usize, protocol: &str, inodes: &HashSet<u64>) -> Vec<sp::SocketEntry> {
+    let content = fs::read_to_string(procfs::pid_path(pid, &format!("net/{}", protocol))).unwrap_or_default();
+    let mut sockets = Vec::new();
+    for line in content.lines().skip(1) { // Skip header
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 10 {
+            continue;
+        }
+        let inode: u64 = parts[9].parse().unwrap_or(0);
+        if !inodes.contains(&inode) {
+            continue;
+        }
+        let (Some((local_address, local_port)), Some((remote_address, remote_port))) =
+            (decode_address(parts[1]), decode_address(parts[2])) else { continue };
+        let (tx, rx) = parts[4].split_once(':').unwrap_or(("0", "0"));
+        sockets.push(sp::SocketEntry {
+            protocol: protocol.to_string(),
+            inode,
+            local_address,
+            local_port,
+            remote_address,
+            remote_port,
+            state: tcp_state(parts[3]),
+            tx_queue: u64::from_str_radix(tx, 16).unwrap_or(0),
+            rx_queue: u64::from_str_radix(rx, 16).unwrap_or(0),
+        });
+    }
+    sockets
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_unix: -input:         a process id and the socket inodes owned by the process
+                    -output:        a vector of UnixSocketEntry (empty if the table is not readable)
+                    -description:   parse "/proc/{PID}/net/unix" and keep only the lines whose inode belongs to the
+                                    process
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_unix(pid: usize, inodes: &HashSet<u64>) -> Vec<sp::UnixSocketEntry> {
+    let content = fs::read_to_string(procfs::pid_path(pid, "net/unix")).unwrap_or_default();
+    let mut sockets = Vec::new();
+    for line in content.lines().skip(1) { // Skip header
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 7 {
+            continue;
+        }
+        let inode: u64 = parts[6].parse().unwrap_or(0);
+        if !inodes.contains(&inode) {
+            continue;
+        }
+        let socket_type = match parts[4] {
+            "0001" => "STREAM",
+            "0002" => "DGRAM",
+            "0005" => "SEQPACKET",
+            _ => "UNKNOWN",
+        }.to_string();
+        let state = match parts[5] {
+            "01" => "UNCONNECTED",
+            "02" => "CONNECTING",
+            "03" => "CONNECTED",
+            "04" => "DISCONNECTING",
+            _ => "UNKNOWN",
+        }.to_string();
+        let path = if parts.len() > 7 { Some(parts[7..].join(" ")) } else { None };
+        let listening = u32::from_str_radix(parts[3], 16).unwrap_or(0) & 0x10000 != 0; // __SO_ACCEPTCON
+        sockets.push(sp::UnixSocketEntry { inode, socket_type, state, listening, path });
+    }
+    sockets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_ipv4() {
+        assert_eq!(decode_address("0100007F:1F90"), Some((String::from("127.0.0.1"), 8080)));
+        assert_eq!(decode_address("00000000:0000"), Some((String::from("0.0.0.0"), 0)));
+        assert_eq!(decode_address("0A01A8C0:01BB"), Some((String::from("192.168.1.10"), 443)));
+    }
+
+    #[test]
+    fn decode_ipv6() {
+        assert_eq!(decode_address("00000000000000000000000001000000:0016"), Some((String::from("::1"), 22)));
+        assert_eq!(decode_address("0000000000000000FFFF00000100007F:0050"), Some((String::from("::ffff:127.0.0.1"), 80)));
+        assert_eq!(decode_address("B80D0120000000000000000001000000:0035"), Some((String::from("2001:db8::1"), 53)));
+    }
+
+    #[test]
+    fn decode_malformed() {
+        assert_eq!(decode_address("0100007F"), None);
+        assert_eq!(decode_address("0100007F:XYZ"), None);
+        assert_eq!(decode_address("0100:0050"), None);
+        assert_eq!(decode_address("0100007F00:0050"), None);
+    }
+
+    #[test]
+    fn tcp_states() {
+        assert_eq!(tcp_state("01"), "ESTABLISHED");
+        assert_eq!(tcp_state("0A"), "LISTEN");
+        assert_eq!(tcp_state("06"), "TIME_WAIT");
+        assert_eq!(tcp_state("0C"), "NEW_SYN_RECV");
+        assert_eq!(tcp_state("FF"), "UNKNOWN");
+        assert_eq!(tcp_state("zz"), "UNKNOWN");
+    }
+}
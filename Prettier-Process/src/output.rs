@@ -0,0 +1,372 @@
+/*
+----------------------------------------------------------------------------------------
+File used to shape and write the output of the commands. A command builds its data once
+(plus its text rendering), then the format (--format) and the destination (--output) are
+resolved here the same way for every command.
+----------------------------------------------------------------------------------------
+*/
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Yaml,
+}
+
+#[derive(Args)]
+pub struct OutputOptions {
+    #[arg(long, value_enum)]
+    pub format: Option<Format>, // text by default, or guessed from the extension of --output
+    #[arg(long)]
+    pub output: Option<PathBuf>, // file to write to, "-" (or nothing) for stdout
+} // Output format and destination shared by the commands
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function records:   -input:         a serialized value
+                    -output:        the records it holds: the elements of an array, or the value itself
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn records(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function flatten:   -input:         a key prefix, a serialized value and the output cells
+                    -output:        /
+                    -description:   append one (key, text) cell per scalar of the value; nested objects get dotted keys
+                                    (ex: "smaps.pss") while arrays are kept as compact JSON in a single cell
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn flatten(prefix: &str, value: &Value, cells: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&key, value, cells);
+            }
+        }
+        Value::String(s) => cells.push((prefix.to_string(), s.clone())),
+        Value::Null => cells.push((prefix.to_string(), String::new())),
+        other => cells.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function flatten_record:    -input:         a serialized record
+                            -output:        its cells (a record that is not an object gets a single "value" cell)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn flatten_record(record: &Value) -> Vec<(String, String)> {
+    let mut cells = Vec::new();
+    flatten(if record.is_object() { "" } else { "value" }, record, &mut cells);
+    cells
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function rows:  -input:         a serialized value
+                -output:        the columns (every key in order of appearance) and one row of cells per record
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn rows(value: Value) -> (Vec<String>, Vec<Vec<String>>) {
+    let records: Vec<Vec<(String, String)>> = records(value).iter().map(flatten_record).collect();
+    let mut columns: Vec<String> = Vec::new();
+    for (key, _) in records.iter().flatten() {
+        if !columns.contains(key) {
+            columns.push(key.clone());
+        }
+    }
+    let rows = records.into_iter()
+        .map(|cells| columns.iter()
+            .map(|c| cells.iter().find(|(k, _)| k == c).map(|(_, v)| v.clone()).unwrap_or_default())
+            .collect())
+        .collect();
+    (columns, rows)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render_table:  -input:         a serialized value
+                        -output:        an aligned text table: one row per record for an array, one "KEY VALUE" row per
+                                        field for a single object
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render_table(value: Value) -> String {
+    let (header, rows) = if value.is_array() {
+        let (columns, rows) = rows(value);
+        (columns.iter().map(|c| c.to_uppercase()).collect(), rows)
+    } else {
+        let rows = flatten_record(&value).into_iter().map(|(k, v)| vec![k, v]).collect();
+        (vec![String::from("KEY"), String::from("VALUE")], rows)
+    };
+    align(&header, &rows)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function align: -input:         the header and the rows of a table (every row has as many cells as the header)
+                -output:        the aligned text table, columns separated by two spaces; widths are counted in
+                                characters so that non-ASCII names stay aligned
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn align(header: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = header.iter().enumerate()
+        .map(|(i, h)| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0).max(h.chars().count()))
+        .collect();
+
+    let line = |cells: &[String]| {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i + 1 == cells.len() {
+                line.push_str(cell); // no trailing padding on the last column
+            } else {
+                line.push_str(&format!("{:<width$}  ", cell, width = widths[i]));
+            }
+        }
+        line.push('\n');
+        line
+    };
+    let mut output = line(header);
+    for row in rows {
+        output.push_str(&line(row));
+    }
+    output
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function csv_line:  -input:         the cells of a line
+                    -output:        the CSV line (RFC 4180 quoting), without line break
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn csv_line(cells: &[String]) -> String {
+    cells.iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render_csv:    -input:         a serialized value
+                        -output:        a CSV document: a header line and one line per record
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render_csv(value: Value) -> String {
+    let (columns, rows) = rows(value);
+    let mut output = csv_line(&columns) + "\n";
+    for row in &rows {
+        output.push_str(&csv_line(row));
+        output.push('\n');
+    }
+    output
+}
+
+impl OutputOptions {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method format:  -input:         /
+                    -output:        the chosen format; without --format it is guessed from the extension of the output
+                                    file (.json, .ndjson/.jsonl, .csv, .yaml/.yml), text else
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn format(&self) -> Format {
+        if let Some(format) = self.format {
+            return format;
+        }
+        match self.output.as_ref().and_then(|p| p.extension()).and_then(|e| e.to_str()) {
+            Some("json") => Format::Json,
+            Some("ndjson") | Some("jsonl") => Format::Ndjson,
+            Some("csv") => Format::Csv,
+            Some("yaml") | Some("yml") => Format::Yaml,
+            _ => Format::Text,
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method file:    -input:         /
+                    -output:        the file to write to; None for stdout
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn file(&self) -> Option<&Path> {
+        self.output.as_deref().filter(|p| *p != Path::new("-"))
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method write:   -input:         the rendered output
+                    -output:        Result type (did it succed or not)
+                    -description:   print the output, or write it to the --output file
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn write(&self, mut output: String) -> Result<(), io::Error> {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        match self.file() {
+            Some(path) => {
+                println!("===== Creating the file =====");
+                fs::write(path, output)?;
+                println!("===== Creation completed =====");
+            }
+            None => print!("{}", output),
+        }
+        Ok(())
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method emit:    -input:         the data of the command and its text rendering (only called in text format)
+                    -output:        Result type (did it succed or not)
+                    -description:   render the data in the chosen format and write it; table and csv get one row per
+                                    element of an array, ndjson one line per element
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn emit<T: Serialize>(&self, data: &T, text: impl FnOnce() -> String) -> Result<(), Box<dyn std::error::Error>> {
+        let output = match self.format() {
+            Format::Text => text(),
+            Format::Table => render_table(serde_json::to_value(data)?),
+            Format::Json => serde_json::to_string_pretty(data)?,
+            Format::Ndjson => records(serde_json::to_value(data)?).iter().map(|r| format!("{}\n", r)).collect(),
+            Format::Csv => render_csv(serde_json::to_value(data)?),
+            Format::Yaml => serde_yaml::to_string(data)?,
+        };
+        Ok(self.write(output)?)
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method stream:  -input:         /
+                    -output:        a Stream writing records one by one (for the commands producing output over time);
+                                    an Error if the --output file cannot be created
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn stream(&self) -> Result<Stream, io::Error> {
+        let (out, terminal): (Box<dyn Write>, bool) = match self.file() {
+            Some(path) => (Box::new(fs::File::create(path)?), false),
+            None => (Box::new(io::stdout()), io::stdout().is_terminal()),
+        };
+        Ok(Stream { out, format: self.format(), header: None, terminal })
+    }
+}
+
+pub struct Stream {
+    out: Box<dyn Write>,
+    format: Format,
+    header: Option<Vec<String>>, // columns of the last CSV header written
+    terminal: bool, // stdout is a terminal (colors can be used)
+} // Output written record by record
+
+impl Stream {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method is_text: -input:         /
+                    -output:        true if the stream is meant to be read by a human (text or table format)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn is_text(&self) -> bool {
+        matches!(self.format, Format::Text | Format::Table)
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method is_terminal: -input:         /
+                        -output:        true if the stream is written to a terminal
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method format:  -input:         /
+                    -output:        the format of the stream
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method text:    -input:         some text
+                    -output:        Result type (did it succed or not)
+                    -description:   write the text in text and table formats, ignore it in the machine formats
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn text(&mut self, text: &str) -> Result<(), io::Error> {
+        if self.is_text() {
+            self.out.write_all(text.as_bytes())?;
+            self.out.flush()?;
+        }
+        Ok(())
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method record:  -input:         a record
+                    -output:        Result type (did it succed or not)
+                    -description:   write the record in the machine formats: one compact JSON line (json and ndjson),
+                                    one YAML document, or one CSV line (preceded by a header each time the columns
+                                    change); ignored in text and table formats
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn record<T: Serialize>(&mut self, record: &T) -> Result<(), Box<dyn std::error::Error>> {
+        match self.format {
+            Format::Text | Format::Table => return Ok(()),
+            Format::Json | Format::Ndjson => writeln!(self.out, "{}", serde_json::to_string(record)?)?,
+            Format::Yaml => write!(self.out, "---\n{}", serde_yaml::to_string(record)?)?,
+            Format::Csv => {
+                let cells = flatten_record(&serde_json::to_value(record)?);
+                let columns: Vec<String> = cells.iter().map(|(k, _)| k.clone()).collect();
+                if self.header.as_ref() != Some(&columns) {
+                    if self.header.is_some() {
+                        writeln!(self.out)?;
+                    }
+                    writeln!(self.out, "{}", csv_line(&columns))?;
+                    self.header = Some(columns);
+                }
+                let values: Vec<String> = cells.into_iter().map(|(_, v)| v).collect();
+                writeln!(self.out, "{}", csv_line(&values))?;
+            }
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_counts_chars() {
+        let header = vec![String::from("NAME"), String::from("PID")];
+        let rows = vec![vec![String::from("ñandú"), String::from("7")], vec![String::from("sh"), String::from("12")]];
+        assert_eq!(align(&header, &rows), "NAME   PID\nñandú  7\nsh     12\n");
+    }
+}
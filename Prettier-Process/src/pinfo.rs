@@ -0,0 +1,293 @@
+use crate::output::OutputOptions;
+use ppsx::struct_proc as sp;
+use ppsx::users::{group_name, user_name};
+use ppsx::Process;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function pinfo: -input:         pid of the wanted process, option as boolean (all) and the output options
+                -output:        Result type (did it succed or not)
+                -description:   open the wanted process (if it exist), then read its info (or its full info with
+                                --all) and display the result in the chosen format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn pinfo(pid: usize, all: bool, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let Ok(process) = Process::new(pid) else {
+        println!("===== PID id not reconized =====");
+        return Ok(());
+    };
+    if all {
+        let info = process.full_info()?;
+        out.emit(&info, || render_all(&info))?;
+    } else {
+        let info = process.info()?;
+        out.emit(&info, || {
+            format!("PID: {}\nName: {}\nState: {}\nParent PID: {}\nUID: {}, GID: {}\nThreads: {}\nMemory (VmRSS): {} kB\nCommand: {}\n",
+                info.pid, info.name, info.state, info.ppid, named(info.uid, user_name(info.uid)), named(info.gid, group_name(info.gid)),
+                info.threads, info.vm_rss, info.cmdline)
+        })?;
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function named: -input:         a uid or gid and its name if known
+                -output:        "ID (NAME)", or the id alone without name
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn named(id: u32, name: Option<String>) -> String {
+    match name {
+        Some(name) => format!("{} ({})", id, name),
+        None => id.to_string(),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function format_inet:   -input:         a TCP/UDP SocketEntry
+                        -output:        a one line description of the socket
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn format_inet(sock: &sp::SocketEntry) -> String {
+    let local = if sock.local_address.contains(':') {
+        format!("[{}]:{}", sock.local_address, sock.local_port)
+    } else {
+        format!("{}:{}", sock.local_address, sock.local_port)
+    };
+    let remote = if sock.remote_address.contains(':') {
+        format!("[{}]:{}", sock.remote_address, sock.remote_port)
+    } else {
+        format!("{}:{}", sock.remote_address, sock.remote_port)
+    };
+    format!("  {:<5} {} -> {} {} (tx: {}, rx: {})\n", sock.protocol, local, remote, sock.state, sock.tx_queue, sock.rx_queue)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render_all:    -input:         FullProcessInfo of the wanted process
+                        -output:        all the information available for the process as text, in different categories
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render_all(info: &sp::FullProcessInfo) -> String {
+    let mut output = String::new();
+    output.push_str("\n--- Basic Information ---\n");
+    output.push_str(&format!("PID: {}\n", info.pid));
+    output.push_str(&format!("Name: {}\n", info.name));
+    output.push_str(&format!("Command: {}\n", info.cmdline));
+    output.push_str("\n--- Status ---\n");
+    output.push_str(&format!("State: {}\n", info.state));
+    output.push_str(&format!("Parent PID: {}\n", info.ppid));
+    output.push_str(&format!("UID: {}, GID: {}\n", named(info.uid, user_name(info.uid)), named(info.gid, group_name(info.gid))));
+    output.push_str(&format!("Threads: {}\n", info.threads));
+    output.push_str(&format!("Priority: {}\n", info.prio));
+    output.push_str(&format!("Nice: {}\n", info.nice));
+    let cred = &info.credentials;
+    let id = |id: &sp::NamedId| named(id.id, id.name.clone());
+    output.push_str("\n--- Credentials ---\n");
+    output.push_str(&format!("UIDs: real {}, effective {}, saved {}, filesystem {}\n", id(&cred.real_uid), id(&cred.effective_uid),
+        id(&cred.saved_uid), id(&cred.fs_uid)));
+    output.push_str(&format!("GIDs: real {}, effective {}, saved {}, filesystem {}\n", id(&cred.real_gid), id(&cred.effective_gid),
+        id(&cred.saved_gid), id(&cred.fs_gid)));
+    if cred.groups.is_empty(){
+        output.push_str("Groups: none\n");
+    }else{
+        output.push_str(&format!("Groups: {}\n", cred.groups.iter().map(id).collect::<Vec<String>>().join(", ")));
+    }
+    if cred.setid{
+        output.push_str("Set-ID: yes (effective ids differ from the real ones)\n");
+    }
+    output.push_str("\n--- CPU Times ---\n");
+    output.push_str(&format!("User time: {} ticks\n", info.utime));
+    output.push_str(&format!("System time: {} ticks\n", info.stime));
+    output.push_str(&format!("Children user time: {} ticks\n", info.cutime));
+    output.push_str(&format!("Children system time: {} ticks\n", info.cstime));
+    output.push_str(&format!("Block I/O delay: {} ticks\n", info.blkio_ticks));
+    output.push_str(&format!("Start time: {} ticks\n", info.sttime));
+    output.push_str(&format!("Last CPU: {}\n", info.processor));
+    output.push_str(&format!("Uptime: {} seconds\n", info.uptime));
+    output.push_str("\n--- Memory ---\n");
+    output.push_str(&format!("VmSize: {} kB\n", info.vm_size));
+    output.push_str(&format!("VmRSS: {} kB\n", info.vm_rss));
+    output.push_str(&format!("VmData: {} kB\n", info.vm_data));
+    output.push_str(&format!("VmStack: {} kB\n", info.vm_stack));
+    output.push_str(&format!("VmExe: {} kB\n", info.vm_exe));
+    output.push_str(&format!("VmLib: {} kB\n", info.vm_lib));
+    output.push_str(&format!("VmSwap: {} kB\n", info.vm_swap));
+    output.push_str(&format!("VmLocked: {} kB\n", info.vm_locked));
+    output.push_str(&format!("VmHWM (Peak RSS): {} kB\n", info.vm_hwm));
+    output.push_str(&format!("VmPeak: {} kB\n", info.vm_peak));
+    if let Some(smaps) = &info.smaps{
+        output.push_str(&format!("PSS (proportional): {} kB\n", smaps.pss));
+        output.push_str(&format!("USS (private): {} kB\n", smaps.uss));
+        output.push_str(&format!("Shared clean/dirty: {} / {} kB\n", smaps.shared_clean, smaps.shared_dirty));
+        output.push_str(&format!("Private clean/dirty: {} / {} kB\n", smaps.private_clean, smaps.private_dirty));
+        output.push_str(&format!("Swap (PSS): {} kB ({} kB)\n", smaps.swap, smaps.swap_pss));
+    }else{
+        output.push_str("PSS/USS: N/A (no permission)\n");
+    }
+    output.push_str(&format!("Minor page faults: {}\n", info.minflt));
+    output.push_str(&format!("Major page faults: {}\n", info.majflt));
+    output.push_str("\n--- I/O Statistics ---\n");
+    if let Some(rb) = info.read_bytes{
+        output.push_str(&format!("Read bytes: {}\n", rb));
+    }else{
+        output.push_str("Read bytes: N/A (no permission)\n");
+    }
+    if let Some(wb) = info.write_bytes{
+        output.push_str(&format!("Write bytes: {}\n", wb));
+    }else{
+        output.push_str("Write bytes: N/A (no permission)\n");
+    }
+    if let Some(rc) = info.read_count{output.push_str(&format!("Read syscalls: {}\n", rc));}
+    if let Some(wc) = info.write_count{output.push_str(&format!("Write syscalls: {}\n", wc));}
+    if let Some(cwb) = info.cancelled_write_bytes{output.push_str(&format!("Cancelled write bytes: {}\n", cwb));}
+    output.push_str("\n--- Files ---\n");
+    output.push_str(&format!("Open file descriptors: {}\n", info.fd_count));
+    output.push_str(&format!("Current working directory: {}\n", info.cwd));
+    output.push_str(&format!("Executable: {}\n", info.exe));
+    output.push_str(&format!("Root directory: {}\n", info.root));
+    if !info.open_files.is_empty(){
+        output.push_str("Open files (first 10):\n");
+        for (i, file) in info.open_files.iter().take(10).enumerate(){
+            output.push_str(&format!("  [{}] {}\n", i, file));
+        }
+        if info.open_files.len() > 10{
+            output.push_str(&format!("  ... and {} more\n", info.open_files.len() - 10));
+        }
+    }
+    output.push_str("\n--- Resource Limits ---\n");
+    output.push_str(&format!("Max CPU time: {}\n", info.mxcpu_time));
+    output.push_str(&format!("Max file size: {}\n", info.mxfile_size));
+    output.push_str(&format!("Max data size: {}\n", info.mxdata_size));
+    output.push_str(&format!("Max stack size: {}\n", info.mxstack_size));
+    output.push_str(&format!("Max core file size: {}\n", info.mxcore_file_size));
+    output.push_str(&format!("Max resident set: {}\n", info.mxresident_set));
+    output.push_str(&format!("Max processes: {}\n", info.mxprocesses));
+    output.push_str(&format!("Max open files: {}\n", info.mxopen_files));
+    output.push_str(&format!("Max locked memory: {}\n", info.mxlocked_memory));
+    output.push_str(&format!("Max address space: {}\n", info.mxaddress_space));
+    output.push_str(&format!("Max file locks: {}\n", info.mxfile_locks));
+    output.push_str(&format!("Max pending signals: {}\n", info.mxpending_signals));
+    output.push_str(&format!("Max msgqueue size: {}\n", info.mxmsgqueue_size));
+    output.push_str(&format!("Max nice priority: {}\n", info.mxnice_prio));
+    output.push_str(&format!("Max realtime priority: {}\n", info.mxrealtime_prio));
+    output.push_str(&format!("Max realtime timeout: {}\n", info.mxrealtime_timeout));
+    output.push_str("\n--- Scheduling ---\n");
+    output.push_str(&format!("Policy: {}\n", info.policy));
+    output.push_str(&format!("RT priority: {}\n", info.rt_prio));
+    output.push_str(&format!("CPU affinity: {}\n", info.cpu_affinity));
+    output.push_str(&format!("I/O priority: {}\n", info.io_priority));
+    if let Some(sw) = info.sched.nr_switches{
+        output.push_str(&format!("Context switches: {} (voluntary: {}, involuntary: {})\n", sw,
+            info.sched.nr_voluntary_switches.unwrap_or(0), info.sched.nr_involuntary_switches.unwrap_or(0)));
+    }else{
+        output.push_str("Context switches: N/A\n");
+    }
+    if let Some(mg) = info.sched.nr_migrations{output.push_str(&format!("CPU migrations: {}\n", mg));}
+    if let Some(rt) = info.sched.sum_exec_runtime_ms{output.push_str(&format!("Total runtime: {:.3} ms\n", rt));}
+    if let Some(ws) = info.sched.wait_sum_ms{
+        output.push_str(&format!("Wait time: {:.3} ms\n", ws));
+    }else{
+        output.push_str("Wait time: N/A (schedstats disabled)\n");
+    }
+    if let Some(vr) = info.sched.vruntime_ms{output.push_str(&format!("Virtual runtime: {:.3} ms\n", vr));}
+    output.push_str("\n--- Network ---\n");
+    if !info.tcp_connections.is_empty(){
+        output.push_str(&format!("TCP connections: {}\n", info.tcp_connections.len()));
+        for sock in &info.tcp_connections{
+            output.push_str(&format_inet(sock));
+        }
+    }else{
+        output.push_str("TCP connections: none\n");
+    }
+    if !info.udp_connections.is_empty(){
+        output.push_str(&format!("UDP connections: {}\n", info.udp_connections.len()));
+        for sock in &info.udp_connections{
+            output.push_str(&format_inet(sock));
+        }
+    }else{
+        output.push_str("UDP connections: none\n");
+    }
+    if !info.unix_sockets.is_empty(){
+        output.push_str(&format!("Unix sockets: {}\n", info.unix_sockets.len()));
+        for sock in &info.unix_sockets{
+            output.push_str(&format!("  [{}] {} {}{} {}\n", sock.inode, sock.socket_type, sock.state,
+                if sock.listening { " LISTEN" } else { "" }, sock.path.as_deref().unwrap_or("(anonymous)")));
+        }
+    }else{
+        output.push_str("Unix sockets: none\n");
+    }
+    output.push_str("\n--- Environment Variables ---\n");
+    if !info.environment.is_empty(){
+        output.push_str(&format!("Count: {}\n", info.environment.len()));
+        output.push_str("First 5:\n");
+        for (i, env) in info.environment.iter().take(5).enumerate(){
+            output.push_str(&format!("  [{}] {}\n", i, env));
+        }
+        if info.environment.len() > 5{
+            output.push_str(&format!("  ... and {} more\n", info.environment.len() - 5));
+        }
+    }else{
+        output.push_str("No environment variables available\n");
+    }
+    output.push_str("\n--- Control Groups ---\n");
+    if !info.cgroups.is_empty(){
+        for cgroup in &info.cgroups{
+            output.push_str(&format!("  {}\n", cgroup));
+        }
+    }else{
+        output.push_str("No cgroup information available\n");
+    }
+    if let Some(cg) = &info.cgroup{
+        let or_na = |value: Option<String>| value.unwrap_or_else(|| String::from("N/A"));
+        output.push_str(&format!("\n--- Cgroup v2 ({}) ---\n", cg.path));
+        output.push_str(&format!("Directory: {}\n", cg.directory));
+        output.push_str(&format!("Memory: {} / {}\n", or_na(cg.memory_current.map(|m| format!("{} kB", m / 1024))),
+            or_na(cg.memory_max.as_ref().map(|m| m.parse::<u64>().map(|m| format!("{} kB", m / 1024)).unwrap_or_else(|_| m.clone())))));
+        output.push_str(&format!("CPU limit: {}\n", match (&cg.cpu_limit, &cg.cpu_max){
+            (Some(limit), Some(max)) => format!("{:.2} CPUs ({})", limit, max),
+            (None, Some(max)) => format!("none ({})", max),
+            _ => String::from("N/A"),
+        }));
+        output.push_str(&format!("Throttled: {} of {} periods, {} ms\n", or_na(cg.nr_throttled.map(|n| n.to_string())),
+            or_na(cg.nr_periods.map(|n| n.to_string())), or_na(cg.throttled_usec.map(|t| (t / 1000).to_string()))));
+        output.push_str(&format!("Processes: {} / {}\n", or_na(cg.pids_current.map(|n| n.to_string())), or_na(cg.pids_max.clone())));
+        for io in &cg.io{
+            output.push_str(&format!("I/O {}: read {} kB ({} ops), written {} kB ({} ops)\n", io.device, io.read_bytes / 1024, io.read_ios,
+                io.write_bytes / 1024, io.write_ios));
+        }
+    }
+    output.push_str("\n--- Container ---\n");
+    output.push_str(&format!("Runtime: {}\n", match (&info.container.runtime, &info.container.id){
+        (Some(runtime), Some(id)) => format!("{} ({})", runtime, id),
+        _ if info.container.differing.iter().any(|ns| ns == "pid") => String::from("unknown (own pid namespace)"),
+        _ => String::from("none detected"),
+    }));
+    for ns in &info.container.namespaces{
+        output.push_str(&format!("  {:<7}{}\n", ns.name, match (ns.inode, ns.differs){
+            (Some(inode), Some(true)) => format!("{} (differs from PID 1)", inode),
+            (Some(inode), Some(false)) => format!("{} (same as PID 1)", inode),
+            (Some(inode), None) => format!("{} (PID 1 not readable)", inode),
+            (None, _) => String::from("N/A"),
+        }));
+    }
+    output.push_str("\n--- NUMA Maps ---\n");
+    if !info.numa_maps.is_empty(){
+        output.push_str(&format!("Count: {} entries\n", info.numa_maps.len()));
+    }else{
+        output.push_str("No NUMA maps available\n");
+    }
+    output.push_str("\n--- Misc ---\n");
+    if let Some(syscall) = &info.syscall{
+        output.push_str(&format!("Current syscall: {}\n", syscall));
+    }else{
+        output.push_str("Current syscall: N/A\n");
+    }
+    if let Some(wchan) = &info.wchan{
+        output.push_str(&format!("Wait channel: {}\n", wchan));
+    }else{
+        output.push_str("Wait channel: N/A\n");
+    }
+    output
+}
@@ -1,7 +1,17 @@
+use crate::cgroup::{cgroup_path, read_cgroup};
+use crate::container::read_container;
+use crate::mem::read_smaps_rollup;
+use crate::net;
+use crate::stat::{parse_stat, read_stat};
+use crate::procfs;
 use crate::struct_proc as sp;
+use crate::users::read_credentials;
 use std::fs;
 use std::io;
 
+pub const IOPRIO_CLASS_SHIFT: i32 = 13; // the I/O priority class is stored above the 13 bits of the level
+pub const IOPRIO_WHO_PROCESS: i32 = 1; // ioprio_get/ioprio_set target a single thread
+
 /*
 ------------------------------------------------------------------------------------------------------------------------
 Function read_info: -input:         a process id
@@ -12,14 +22,16 @@ Function read_info: -input:         a process id
 ------------------------------------------------------------------------------------------------------------------------
 */
 pub fn read_info(pid: usize) -> Result<sp::ProcessInfo, io::Error> {
-    let content = format!("/proc/{}/comm", pid);
+    let content = procfs::pid_path(pid, "comm");
     let name = fs::read_to_string(content)?.trim().to_string();
-    let status = fs::read_to_string(format!("/proc/{}/status", pid))?;
+    let status = fs::read_to_string(procfs::pid_path(pid, "status"))?;
 
     let mut state = String::from("None");
     let mut ppid = 0;
     let mut uid = 0;
     let mut gid = 0;
+    let mut euid = 0;
+    let mut egid = 0;
     let mut threads = 0;
     let mut vm_size = 0;
     let mut vm_rss = 0;
@@ -28,18 +40,25 @@ pub fn read_info(pid: usize) -> Result<sp::ProcessInfo, io::Error> {
         match now.next().unwrap_or("") {
             "State:" => {state = now.next().unwrap_or("None").to_string();}
             "PPid:" => {ppid = now.next().unwrap_or("0").parse().unwrap_or(0);}
-            "Uid:" => {uid = now.next().unwrap_or("0").parse().unwrap_or(0);}
-            "Gid:" => {gid = now.next().unwrap_or("0").parse().unwrap_or(0);}
+            // real then effective, saved and filesystem ids
+            "Uid:" => {
+                uid = now.next().unwrap_or("0").parse().unwrap_or(0);
+                euid = now.next().and_then(|v| v.parse().ok()).unwrap_or(uid);
+            }
+            "Gid:" => {
+                gid = now.next().unwrap_or("0").parse().unwrap_or(0);
+                egid = now.next().and_then(|v| v.parse().ok()).unwrap_or(gid);
+            }
             "Threads:" => {threads = now.next().unwrap_or("0").parse().unwrap_or(0);}
             "VmSize:" => {vm_size = now.next().unwrap_or("0").parse().unwrap_or(0);}
             "VmRSS:" => {vm_rss = now.next().unwrap_or("0").parse().unwrap_or(0);}
             _ => {}
         }
     }
-    let cmdline_path = format!("/proc/{}/cmdline", pid);
+    let cmdline_path = procfs::pid_path(pid, "cmdline");
     let cmdline = fs::read_to_string(cmdline_path).unwrap_or_default().replace('\0', " ").trim().to_string();
 
-    Ok(sp::ProcessInfo{pid, name, state, ppid, uid, gid, threads, vm_size, vm_rss, cmdline,})
+    Ok(sp::ProcessInfo{pid, name, state, ppid, uid, gid, euid, egid, threads, vm_size, vm_rss, cmdline,})
 }
 
 /*
@@ -52,8 +71,8 @@ Function read_all_info: -input:         a process id
 ------------------------------------------------------------------------------------------------------------------------
 */
 pub fn read_all_info(pid: usize) -> Result<sp::FullProcessInfo, io::Error> {
-    let name = fs::read_to_string(format!("/proc/{}/comm", pid))?.trim().to_string();
-    let status = fs::read_to_string(format!("/proc/{}/status", pid))?;
+    let name = fs::read_to_string(procfs::pid_path(pid, "comm"))?.trim().to_string();
+    let status = fs::read_to_string(procfs::pid_path(pid, "status"))?;
     let mut state = String::from("None");
     let mut ppid = 0;
     let mut uid = 0;
@@ -92,51 +111,42 @@ pub fn read_all_info(pid: usize) -> Result<sp::FullProcessInfo, io::Error> {
         }
     }
     
-    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
-    let stat_parts: Vec<&str> = stat.split_whitespace().collect();
-    let utime = stat_parts.get(13).and_then(|s| s.parse().ok()).unwrap_or(0);
-    let sttime = stat_parts.get(14).and_then(|s| s.parse().ok()).unwrap_or(0);
-    let prio = stat_parts.get(17).and_then(|s| s.parse().ok()).unwrap_or(0);
-    let nice = stat_parts.get(18).and_then(|s| s.parse().ok()).unwrap_or(0);
-    let stime = stat_parts.get(21).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let stat = read_stat(pid)?;
+    let utime = stat.utime;
+    let stime = stat.stime;
+    let cutime = stat.cutime;
+    let cstime = stat.cstime;
+    let sttime = stat.starttime;
+    let prio = stat.priority as i32;
+    let nice = stat.nice as i32;
+    let minflt = stat.minflt;
+    let majflt = stat.majflt;
+    let processor = stat.processor;
+    let blkio_ticks = stat.delayacct_blkio_ticks;
+    let rt_prio = stat.rt_priority;
+    let policy = policy_name(stat.policy);
     
-    let uptime = fs::read_to_string("/proc/uptime")
-        .ok()
-        .and_then(|s| s.split_whitespace().next().and_then(|t| t.parse::<f64>().ok()))
-        .unwrap_or(0.0) as u64;
+    let uptime = read_uptime() as u64;
+    let smaps = read_smaps_rollup(pid);
     
-    let cmdline = fs::read_to_string(format!("/proc/{}/cmdline", pid))
+    let cmdline = fs::read_to_string(procfs::pid_path(pid, "cmdline"))
         .unwrap_or_default()
         .replace('\0', " ")
         .trim()
         .to_string();
     
-    let io_result = fs::read_to_string(format!("/proc/{}/io", pid));
-    let mut read_bytes = None;
-    let mut write_bytes = None;
-    let mut read_count = None;
-    let mut write_count = None;
-    let mut cancelled_write_bytes = None;
+    let io = read_io(pid).ok();
+    let read_bytes = io.as_ref().map(|io| io.read_bytes);
+    let write_bytes = io.as_ref().map(|io| io.write_bytes);
+    let read_count = io.as_ref().map(|io| io.read_count);
+    let write_count = io.as_ref().map(|io| io.write_count);
+    let cancelled_write_bytes = io.as_ref().map(|io| io.cancelled_write_bytes);
     
-    if let Ok(io_content) = io_result {
-        for line in io_content.lines() {
-            let mut parts = line.split_whitespace();
-            match parts.next().unwrap_or("") {
-                "read_bytes:" => { read_bytes = parts.next().and_then(|s| s.parse().ok()); }
-                "write_bytes:" => { write_bytes = parts.next().and_then(|s| s.parse().ok()); }
-                "syscr:" => { read_count = parts.next().and_then(|s| s.parse().ok()); }
-                "syscw:" => { write_count = parts.next().and_then(|s| s.parse().ok()); }
-                "cancelled_write_bytes:" => { cancelled_write_bytes = parts.next().and_then(|s| s.parse().ok()); }
-                _ => {}
-            }
-        }
-    }
-    
-    let fd_count = fs::read_dir(format!("/proc/{}/fd", pid))
+    let fd_count = fs::read_dir(procfs::pid_path(pid, "fd"))
         .map(|entries| entries.count())
         .unwrap_or(0);
     
-    let open_files = fs::read_dir(format!("/proc/{}/fd", pid))
+    let open_files = fs::read_dir(procfs::pid_path(pid, "fd"))
         .map(|entries| {
             entries.filter_map(|e| e.ok())
                 .filter_map(|e| fs::read_link(e.path()).ok())
@@ -145,22 +155,22 @@ pub fn read_all_info(pid: usize) -> Result<sp::FullProcessInfo, io::Error> {
         })
         .unwrap_or_else(|_| Vec::new());
     
-    let cwd = fs::read_link(format!("/proc/{}/cwd", pid))
+    let cwd = fs::read_link(procfs::pid_path(pid, "cwd"))
         .ok()
         .and_then(|p| p.to_str().map(|s| s.to_string()))
         .unwrap_or_else(|| String::from("N/A"));
     
-    let exe = fs::read_link(format!("/proc/{}/exe", pid))
+    let exe = fs::read_link(procfs::pid_path(pid, "exe"))
         .ok()
         .and_then(|p| p.to_str().map(|s| s.to_string()))
         .unwrap_or_else(|| String::from("N/A"));
     
-    let root = fs::read_link(format!("/proc/{}/root", pid))
+    let root = fs::read_link(procfs::pid_path(pid, "root"))
         .ok()
         .and_then(|p| p.to_str().map(|s| s.to_string()))
         .unwrap_or_else(|| String::from("N/A"));
     
-    let limits_content = fs::read_to_string(format!("/proc/{}/limits", pid)).unwrap_or_default();
+    let limits_content = fs::read_to_string(procfs::pid_path(pid, "limits")).unwrap_or_default();
     let mut limits_map = std::collections::HashMap::new();
     for line in limits_content.lines().skip(1) { // Skip header
         let parts: Vec<&str> = line.split_whitespace().collect();
@@ -188,14 +198,18 @@ pub fn read_all_info(pid: usize) -> Result<sp::FullProcessInfo, io::Error> {
     let mxrealtime_prio = limits_map.get("Max realtime prio").cloned().unwrap_or_else(|| String::from("0"));
     let mxrealtime_timeout = limits_map.get("Max realtime timeout").cloned().unwrap_or_else(|| String::from("unlimited"));
     
-    let tcp_connections = Vec::new(); // TODO: parser /proc/net/tcp
-    let udp_connections = Vec::new(); // TODO: parser /proc/net/udp
-    let unix_sockets = Vec::new(); // TODO: parser /proc/net/unix
+    let inodes = net::socket_inodes(pid);
+    let mut tcp_connections = net::read_inet(pid, "tcp", &inodes);
+    tcp_connections.extend(net::read_inet(pid, "tcp6", &inodes));
+    let mut udp_connections = net::read_inet(pid, "udp", &inodes);
+    udp_connections.extend(net::read_inet(pid, "udp6", &inodes));
+    let unix_sockets = net::read_unix(pid, &inodes);
     
-    let policy = String::from("SCHED_OTHER"); // Par défaut, à parser depuis /proc/[pid]/sched
-    let rt_prio = 0; // À parser depuis /proc/[pid]/stat
+    let sched = read_sched(pid);
+    let cpu_affinity = cpu_affinity(pid);
+    let io_priority = io_priority(pid);
     
-    let environment = fs::read_to_string(format!("/proc/{}/environ", pid))
+    let environment = fs::read_to_string(procfs::pid_path(pid, "environ"))
         .map(|content| {
             content.split('\0')
                 .filter(|s| !s.is_empty())
@@ -204,317 +218,234 @@ pub fn read_all_info(pid: usize) -> Result<sp::FullProcessInfo, io::Error> {
         })
         .unwrap_or_else(|_| Vec::new());
     
-    let numa_maps = fs::read_to_string(format!("/proc/{}/numa_maps", pid))
+    let numa_maps = fs::read_to_string(procfs::pid_path(pid, "numa_maps"))
         .map(|content| content.lines().map(|s| s.to_string()).collect())
         .unwrap_or_else(|_| Vec::new());
     
-    let cgroups = fs::read_to_string(format!("/proc/{}/cgroup", pid))
+    let cgroups = fs::read_to_string(procfs::pid_path(pid, "cgroup"))
         .map(|content| content.lines().map(|s| s.to_string()).collect())
         .unwrap_or_else(|_| Vec::new());
+    let cgroup = cgroup_path(pid).map(|path| read_cgroup(&path));
+    let container = read_container(pid);
+    let credentials = read_credentials(pid)?;
     
-    let syscall = fs::read_to_string(format!("/proc/{}/syscall", pid))
+    let syscall = fs::read_to_string(procfs::pid_path(pid, "syscall"))
         .ok()
         .map(|s| s.trim().to_string());
     
 
-    let wchan = fs::read_to_string(format!("/proc/{}/wchan", pid))
+    let wchan = fs::read_to_string(procfs::pid_path(pid, "wchan"))
         .ok()
         .map(|s| s.trim().to_string());
     
-    Ok(sp::FullProcessInfo { pid, name, cmdline, state, ppid, threads, uid, gid, utime, stime, prio, nice, vm_size, vm_rss, vm_data, vm_stack, vm_exe, vm_lib, vm_swap, vm_locked,
-        vm_hwm, vm_peak, read_bytes, write_bytes, read_count, write_count, cancelled_write_bytes, fd_count, open_files, cwd, exe, root, mxcpu_time, mxfile_size, mxdata_size, mxstack_size, mxcore_file_size,
+    Ok(sp::FullProcessInfo { pid, name, cmdline, state, ppid, threads, uid, gid, utime, stime, cutime, cstime, prio, nice, processor, minflt, majflt, blkio_ticks, vm_size, vm_rss, vm_data, vm_stack, vm_exe, vm_lib, vm_swap, vm_locked,
+        vm_hwm, vm_peak, smaps, read_bytes, write_bytes, read_count, write_count, cancelled_write_bytes, fd_count, open_files, cwd, exe, root, mxcpu_time, mxfile_size, mxdata_size, mxstack_size, mxcore_file_size,
         mxresident_set, mxprocesses, mxopen_files, mxlocked_memory, mxaddress_space, mxfile_locks, mxpending_signals, mxmsgqueue_size, mxnice_prio, mxrealtime_prio, mxrealtime_timeout,
-        tcp_connections, udp_connections, unix_sockets, policy, rt_prio, environment, numa_maps, cgroups, syscall, wchan, sttime, uptime})
+        tcp_connections, udp_connections, unix_sockets, policy, rt_prio, sched, cpu_affinity, io_priority, environment, numa_maps, cgroups, cgroup, container, credentials, syscall, wchan, sttime, uptime})
 }
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function get_pids_info: -input:         /
-                        -output:        a vector of "Process" containing the pid and name of all process
-                        -description:   get the pid of all processes (with "/proc") then for every process,
-                                        find is name in "/proc/{PID}/comm" and push both information in a vector 
+Function read_io:   -input:         a process id
+                    -output:        the IoCounters of this process; an Error if /proc/{PID}/io is not readable (it needs
+                                    the same privileges as ptrace)
 ------------------------------------------------------------------------------------------------------------------------
 */
-fn get_pids_info() -> Result<Vec<sp::Process>, io::Error> {
-    let mut pids: Vec<sp::Process> = Vec::new();
-
-    let contents = fs::read_dir("/proc")?;
+pub fn read_io(pid: usize) -> Result<sp::IoCounters, io::Error> {
+    let content = fs::read_to_string(procfs::pid_path(pid, "io"))?;
+    let mut counters = sp::IoCounters { read_bytes: 0, write_bytes: 0, read_count: 0, write_count: 0, cancelled_write_bytes: 0 };
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let field = match parts.next().unwrap_or("") {
+            "read_bytes:" => &mut counters.read_bytes,
+            "write_bytes:" => &mut counters.write_bytes,
+            "syscr:" => &mut counters.read_count,
+            "syscw:" => &mut counters.write_count,
+            "cancelled_write_bytes:" => &mut counters.cancelled_write_bytes,
+            _ => continue,
+        };
+        *field = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    }
+    Ok(counters)
+}
 
-    for instance in contents {
-        let instance = instance?;
-        let instance = instance.file_name().into_string().unwrap();
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function policy_name:   -input:         a scheduling policy number (as in /proc/{PID}/stat)
+                        -output:        the name of the policy (as in include/uapi/linux/sched.h)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn policy_name(policy: u32) -> String {
+    match policy {
+        0 => String::from("SCHED_OTHER"),
+        1 => String::from("SCHED_FIFO"),
+        2 => String::from("SCHED_RR"),
+        3 => String::from("SCHED_BATCH"),
+        5 => String::from("SCHED_IDLE"),
+        6 => String::from("SCHED_DEADLINE"),
+        7 => String::from("SCHED_EXT"),
+        other => format!("UNKNOWN ({})", other),
+    }
+}
 
-        if let Ok(pid) = instance.parse::<usize>() {
-            let comm_path = format!("/proc/{}/comm", pid);
-            if let Ok(name) = fs::read_to_string(comm_path) {
-                pids.push(sp::Process {
-                    pid,
-                    name: name.trim().to_string(),
-                });
-            }
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_sched:    -input:         a process id
+                        -output:        a SchedInfo (every field is None if the kernel does not expose it)
+                        -description:   parse the "key : value" lines of /proc/{PID}/sched; this file only exists with
+                                        CONFIG_SCHED_DEBUG and the wait statistics need schedstats enabled
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_sched(pid: usize) -> sp::SchedInfo {
+    let mut sched = sp::SchedInfo {
+        nr_switches: None,
+        nr_voluntary_switches: None,
+        nr_involuntary_switches: None,
+        nr_migrations: None,
+        sum_exec_runtime_ms: None,
+        wait_sum_ms: None,
+        vruntime_ms: None,
+    };
+    let content = fs::read_to_string(procfs::pid_path(pid, "sched")).unwrap_or_default();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        match key.trim() {
+            "nr_switches" => { sched.nr_switches = value.parse().ok(); }
+            "nr_voluntary_switches" => { sched.nr_voluntary_switches = value.parse().ok(); }
+            "nr_involuntary_switches" => { sched.nr_involuntary_switches = value.parse().ok(); }
+            "se.nr_migrations" => { sched.nr_migrations = value.parse().ok(); }
+            "se.sum_exec_runtime" => { sched.sum_exec_runtime_ms = value.parse().ok(); }
+            "se.vruntime" => { sched.vruntime_ms = value.parse().ok(); }
+            // "se.statistics.wait_sum" on older kernels, "stats.wait_sum" on newer ones
+            k if k.ends_with(".wait_sum") => { sched.wait_sum_ms = value.parse().ok(); }
+            _ => {}
         }
     }
-    Ok(pids)
+    sched
 }
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function list_proc: -input:         options as booleans (json and file)
-                    -output:        Result type (did it succed or not)
-                    -description:   call get_pids_info and display the result dependig on the options
+Function ioprio_get:    -input:         a thread id
+                        -output:        its raw I/O priority (class << 13 | level); an Error if the syscall failed
 ------------------------------------------------------------------------------------------------------------------------
 */
-pub fn list_proc(json: bool, file: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let proc = match get_pids_info() {
-        Ok(p) => p,
-        Err(_) => {
-            println!("No process running?!");
-            let v: Vec<sp::Process> = Vec::new();
-            v
-        }
-    };
+fn ioprio_get(tid: usize) -> Result<i32, io::Error> {
+    let prio = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, tid as libc::c_int) };
+    if prio < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(prio as i32)
+}
 
-    if json {
-        let output = serde_json::to_string_pretty(&proc)?;
-        if !file {
-            println!("{}", output);
-        } else {
-            println!("===== Creating the file =====");
-            fs::write("./processes.json", output)?;
-            println!("===== Creation completed =====");
-        }
-    } else {
-        if !file {
-            for p in &proc {
-                println!("PID: {} - {}", p.pid, p.name);
-            }
-        } else {
-            println!("===== Creating the file =====");
-            let mut output = String::new();
-            for p in &proc {
-                output.push_str(&format!("PID: {} - {}\n", p.pid, p.name));
-            }
-            fs::write("./processes.txt", output)?;
-            println!("===== Creation completed =====");
-        }
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function io_priority:   -input:         a process id
+                        -output:        its I/O priority as "class/level" (ex: "best-effort/4"); "N/A" if unreadable or
+                                        if the procfs root is not the live /proc (the syscall would read another process)
+                        -description:   a process without class ("none") gets a best-effort level derived from its
+                                        nice value, see ioprio_set(2)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn io_priority(pid: usize) -> String {
+    if !procfs::is_live() {
+        return String::from("N/A");
+    }
+    let Ok(prio) = ioprio_get(pid) else { return String::from("N/A") };
+    let level = prio & ((1 << IOPRIO_CLASS_SHIFT) - 1);
+    match prio >> IOPRIO_CLASS_SHIFT {
+        0 => String::from("none (best-effort from nice)"),
+        1 => format!("realtime/{}", level),
+        2 => format!("best-effort/{}", level),
+        3 => String::from("idle"),
+        other => format!("unknown class {}/{}", other, level),
     }
-    Ok(())
 }
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function pinfo: -input:         pid of the wanted process and options as booleans (json and file)
-                -output:        Result type (did it succed or not)
-                -description:   call get_pids_info to check if the wanted process exist, then call read_info on
-                                its pid and display the result depending on the options
+Function cpu_affinity:  -input:         a thread (or process) id
+                        -output:        the cpus it may run on, as in /proc/{PID}/status (ex: "0-3,6")
 ------------------------------------------------------------------------------------------------------------------------
 */
-pub fn pinfo(pid: usize, json: bool, file: bool, all: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let proc = match get_pids_info() {
-        Ok(p) => p,
-        Err(_) => {
-            println!("No process running?!");
-            let v: Vec<sp::Process> = Vec::new();
-            v
-        }
-    };
-    let is_in = proc.iter().any(|p| p.pid == pid);
-    if !is_in {
-        println!("===== PID id not reconized =====");
-    } else {
-        if all{
-            let info = read_all_info(pid)?;
-            display_all(info, json, file)?;
-        }
-        else{
-            let info = read_info(pid)?;
-            if json {
-                let output = serde_json::to_string_pretty(&info)?;
-                if file {
-                    println!("===== Creating the file =====");
-                    fs::write(format!("./processes_{pid}_info.json"), output)?;
-                    println!("===== Creation completed =====");
-                } else {
-                    println!("{}", output);
-                }
-            } else {
-                if !file {
-                    println!("PID: {}", info.pid);
-                    println!("Name: {}", info.name);
-                    println!("State: {}", info.state);
-                    println!("Parent PID: {}", info.ppid);
-                    println!("UID: {}, GID: {}", info.uid, info.gid);
-                    println!("Threads: {}", info.threads);
-                    println!("Memory (VmRSS): {} kB", info.vm_rss);
-                    println!("Command: {}", info.cmdline);
-                } else {
-                    println!("===== Creating the file =====");
-                    let output = "PID: {info.pid}\nName: {info.name}\nState: {info.state}\nParent PID: {info.ppid}\nUID: {info.uid}, GID: {info.gid}\nThreads: {info.threads}\nVmSize: {info.vm_size}\nMemory (VmRSS): {info.vm_rss} kB\nCommand: {info.cmdline}";
-                    fs::write(format!("./processes_{pid}_info.txt"), output)?;
-                    println!("===== Creation completed =====");
-                }
-            }
+pub fn cpu_affinity(tid: usize) -> String {
+    fs::read_to_string(procfs::pid_path(tid, "status"))
+        .unwrap_or_default()
+        .lines()
+        .find_map(|line| line.strip_prefix("Cpus_allowed_list:").map(|v| v.trim().to_string()))
+        .unwrap_or_else(|| String::from("N/A"))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function get_all_info:  -input:         /
+                        -output:        a vector of ProcessInfo for every process readable under the procfs root
+                        -description:   list the numerical entries of "/proc" and call read_info on each of them,
+                                        skipping the processes that vanished in between
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn get_all_info() -> Result<Vec<sp::ProcessInfo>, io::Error> {
+    let mut pids: Vec<sp::ProcessInfo> = Vec::new();
+
+    let contents = fs::read_dir(procfs::root())?;
+
+    for instance in contents {
+        let instance = instance?;
+        let instance = instance.file_name().into_string().unwrap_or_default();
+
+        if let Ok(pid) = instance.parse::<usize>()
+            && let Ok(info) = read_info(pid) {
+            pids.push(info);
         }
     }
-    Ok(())
+    Ok(pids)
 }
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function display_all:   -input:         FullProcessInfo of the wanted process and options as booleans (json and file)
-                        -output:        Result type (did it succed or not)
-                        -description:   display all the information available for the wanted process in different
-                                        categories in txt mode according to the different options (--file and --json)
+Function read_threads:  -input:         a process id
+                        -output:        a ThreadInfo for every thread of this process (sorted by tid); an Error if the
+                                        process does not exist
+                        -description:   read /proc/{PID}/task/{TID}/comm, stat and status for each entry of the task
+                                        directory; a thread that ends while being read is skipped
 ------------------------------------------------------------------------------------------------------------------------
 */
-fn display_all(info: sp::FullProcessInfo, json: bool, file: bool) -> Result<(), io::Error> {
-    if json {
-        let output = serde_json::to_string_pretty(&info)?;
-        if file{
-            println!("===== Creating the file =====");
-            fs::write(format!("./processes_{}_all_info.json", info.pid), output)?;
-            println!("===== Creation completed =====");
-        }else{
-            println!("{}", output);
-        }
-    }else{
-        let mut output = String::new();
-        output.push_str("\n--- Basic Information ---\n");
-        output.push_str(&format!("PID: {}\n", info.pid));
-        output.push_str(&format!("Name: {}\n", info.name));
-        output.push_str(&format!("Command: {}\n", info.cmdline));
-        output.push_str("\n--- Status ---\n");
-        output.push_str(&format!("State: {}\n", info.state));
-        output.push_str(&format!("Parent PID: {}\n", info.ppid));
-        output.push_str(&format!("UID: {}, GID: {}\n", info.uid, info.gid));
-        output.push_str(&format!("Threads: {}\n", info.threads));
-        output.push_str(&format!("Priority: {}\n", info.prio));
-        output.push_str(&format!("Nice: {}\n", info.nice));
-        output.push_str("\n--- CPU Times ---\n");
-        output.push_str(&format!("User time: {} ticks\n", info.utime));
-        output.push_str(&format!("System time: {} ticks\n", info.stime));
-        output.push_str(&format!("Start time: {} ticks\n", info.sttime));
-        output.push_str(&format!("Uptime: {} seconds\n", info.uptime));
-        output.push_str("\n--- Memory ---\n");
-        output.push_str(&format!("VmSize: {} kB\n", info.vm_size));
-        output.push_str(&format!("VmRSS: {} kB\n", info.vm_rss));
-        output.push_str(&format!("VmData: {} kB\n", info.vm_data));
-        output.push_str(&format!("VmStack: {} kB\n", info.vm_stack));
-        output.push_str(&format!("VmExe: {} kB\n", info.vm_exe));
-        output.push_str(&format!("VmLib: {} kB\n", info.vm_lib));
-        output.push_str(&format!("VmSwap: {} kB\n", info.vm_swap));
-        output.push_str(&format!("VmLocked: {} kB\n", info.vm_locked));
-        output.push_str(&format!("VmHWM (Peak RSS): {} kB\n", info.vm_hwm));
-        output.push_str(&format!("VmPeak: {} kB\n", info.vm_peak));
-        output.push_str("\n--- I/O Statistics ---\n");
-        if let Some(rb) = info.read_bytes{
-            output.push_str(&format!("Read bytes: {}\n", rb));
-        }else{
-            output.push_str("Read bytes: N/A (no permission)\n");
-        }
-        if let Some(wb) = info.write_bytes{
-            output.push_str(&format!("Write bytes: {}\n", wb));
-        }else{
-            output.push_str("Write bytes: N/A (no permission)\n");
-        }
-        if let Some(rc) = info.read_count{output.push_str(&format!("Read syscalls: {}\n", rc));}
-        if let Some(wc) = info.write_count{output.push_str(&format!("Write syscalls: {}\n", wc));}
-        if let Some(cwb) = info.cancelled_write_bytes{output.push_str(&format!("Cancelled write bytes: {}\n", cwb));}
-        output.push_str("\n--- Files ---\n");
-        output.push_str(&format!("Open file descriptors: {}\n", info.fd_count));
-        output.push_str(&format!("Current working directory: {}\n", info.cwd));
-        output.push_str(&format!("Executable: {}\n", info.exe));
-        output.push_str(&format!("Root directory: {}\n", info.root));
-        if !info.open_files.is_empty(){
-            output.push_str("Open files (first 10):\n");
-            for (i, file) in info.open_files.iter().take(10).enumerate(){
-                output.push_str(&format!("  [{}] {}\n", i, file));
-            }
-            if info.open_files.len() > 10{
-                output.push_str(&format!("  ... and {} more\n", info.open_files.len() - 10));
-            }
-        }
-        output.push_str("\n--- Resource Limits ---\n");
-        output.push_str(&format!("Max CPU time: {}\n", info.mxcpu_time));
-        output.push_str(&format!("Max file size: {}\n", info.mxfile_size));
-        output.push_str(&format!("Max data size: {}\n", info.mxdata_size));
-        output.push_str(&format!("Max stack size: {}\n", info.mxstack_size));
-        output.push_str(&format!("Max core file size: {}\n", info.mxcore_file_size));
-        output.push_str(&format!("Max resident set: {}\n", info.mxresident_set));
-        output.push_str(&format!("Max processes: {}\n", info.mxprocesses));
-        output.push_str(&format!("Max open files: {}\n", info.mxopen_files));
-        output.push_str(&format!("Max locked memory: {}\n", info.mxlocked_memory));
-        output.push_str(&format!("Max address space: {}\n", info.mxaddress_space));
-        output.push_str(&format!("Max file locks: {}\n", info.mxfile_locks));
-        output.push_str(&format!("Max pending signals: {}\n", info.mxpending_signals));
-        output.push_str(&format!("Max msgqueue size: {}\n", info.mxmsgqueue_size));
-        output.push_str(&format!("Max nice priority: {}\n", info.mxnice_prio));
-        output.push_str(&format!("Max realtime priority: {}\n", info.mxrealtime_prio));
-        output.push_str(&format!("Max realtime timeout: {}\n", info.mxrealtime_timeout));
-        output.push_str("\n--- Scheduling ---\n");
-        output.push_str(&format!("Policy: {}\n", info.policy));
-        output.push_str(&format!("RT priority: {}\n", info.rt_prio));
-        output.push_str("\n--- Network ---\n");
-        if !info.tcp_connections.is_empty(){
-            output.push_str(&format!("TCP connections: {}\n", info.tcp_connections.len()));
-        }else{
-            output.push_str("TCP connections: none\n");
-        }
-        if !info.udp_connections.is_empty(){
-            output.push_str(&format!("UDP connections: {}\n", info.udp_connections.len()));
-        }else{
-            output.push_str("UDP connections: none\n");
-        }
-        if !info.unix_sockets.is_empty(){
-            output.push_str(&format!("Unix sockets: {}\n", info.unix_sockets.len()));
-        }else{
-            output.push_str("Unix sockets: none\n");
-        }
-        output.push_str("\n--- Environment Variables ---\n");
-        if !info.environment.is_empty(){
-            output.push_str(&format!("Count: {}\n", info.environment.len()));
-            output.push_str("First 5:\n");
-            for (i, env) in info.environment.iter().take(5).enumerate(){
-                output.push_str(&format!("  [{}] {}\n", i, env));
-            }
-            if info.environment.len() > 5{
-                output.push_str(&format!("  ... and {} more\n", info.environment.len() - 5));
-            }
-        }else{
-            output.push_str("No environment variables available\n");
-        }
-        output.push_str("\n--- Control Groups ---\n");
-        if !info.cgroups.is_empty(){
-            for cgroup in &info.cgroups{
-                output.push_str(&format!("  {}\n", cgroup));
-            }
-        }else{
-            output.push_str("No cgroup information available\n");
-        }
-        output.push_str("\n--- NUMA Maps ---\n");
-        if !info.numa_maps.is_empty(){
-            output.push_str(&format!("Count: {} entries\n", info.numa_maps.len()));
-        }else{
-            output.push_str("No NUMA maps available\n");
-        }
-        output.push_str("\n--- Misc ---\n");
-        if let Some(syscall) = &info.syscall{
-            output.push_str(&format!("Current syscall: {}\n", syscall));
-        }else{
-            output.push_str("Current syscall: N/A\n");
-        }
-        if let Some(wchan) = &info.wchan{
-            output.push_str(&format!("Wait channel: {}\n", wchan));
-        }else{
-            output.push_str("Wait channel: N/A\n");
-        }
-        if file{
-            println!("===== Creating the file =====");
-            fs::write(format!("./processes_{}_all_info.txt", info.pid), output)?;
-            println!("===== Creation completed =====");
-        }else{
-            print!("{}", output);
-        }
+pub fn read_threads(pid: usize) -> Result<Vec<sp::ThreadInfo>, io::Error> {
+    let mut threads: Vec<sp::ThreadInfo> = Vec::new();
+    for entry in fs::read_dir(procfs::pid_path(pid, "task"))? {
+        let Some(tid) = entry?.file_name().to_str().and_then(|name| name.parse::<usize>().ok()) else { continue };
+        let task = |file: &str| fs::read_to_string(procfs::pid_path(pid, &format!("task/{}/{}", tid, file)));
+        let (Ok(comm), Ok(stat), Ok(status)) = (task("comm"), task("stat"), task("status")) else { continue };
+        let Some(stat) = parse_stat(&stat) else { continue };
+        let switches = |key: &str| status.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0);
+        threads.push(sp::ThreadInfo {
+            tid,
+            name: comm.trim().to_string(),
+            state: stat.state.to_string(),
+            cpu_ticks: stat.utime + stat.stime,
+            last_cpu: stat.processor,
+            voluntary_switches: switches("voluntary_ctxt_switches:"),
+            nonvoluntary_switches: switches("nonvoluntary_ctxt_switches:"),
+        });
     }
-    Ok(())
-}
\ No newline at end of file
+    threads.sort_by_key(|t| t.tid);
+    Ok(threads)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_uptime:   -input:         /
+                        -output:        the system uptime in seconds (0 if unreadable)
+                        -description:   read the first value of "/proc/uptime"
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_uptime() -> f64 {
+    fs::read_to_string(procfs::path("uptime"))
+        .ok()
+        .and_then(|s| s.split_whitespace().next().and_then(|t| t.parse::<f64>().ok()))
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,179 @@
+/*
+----------------------------------------------------------------------------------------
+File used to expose the entry points of the library: the processes iterator, the Process
+handle and the cpu sampler. They wrap the readers of the other files and turn their I/O
+errors into typed Errors.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::error::{Error, Result};
+use crate::mem;
+use crate::proc;
+use crate::procfs;
+use crate::stat;
+use crate::struct_proc as sp;
+use crate::users;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+
+/// Handle on a process, identified by its pid.
+///
+/// The handle does not keep anything open: every method reads the process again under
+/// [`procfs::root`], so a process that exited in between gives [`Error::NotFound`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Process {
+    pid: usize,
+}
+
+impl Process {
+    /// Opens the process `pid`, failing with [`Error::NotFound`] if it does not exist.
+    pub fn new(pid: usize) -> Result<Process> {
+        let process = Process { pid };
+        if !process.exists() {
+            return Err(Error::NotFound(pid));
+        }
+        Ok(process)
+    }
+
+    /// Pid of the process.
+    pub fn pid(&self) -> usize {
+        self.pid
+    }
+
+    /// Whether the process still exists.
+    pub fn exists(&self) -> bool {
+        procfs::pid_path(self.pid, "stat").exists()
+    }
+
+    /// Main information of the process (name, state, parent, owner, threads, memory, command line),
+    /// read from `/proc/PID/status`.
+    pub fn info(&self) -> Result<sp::ProcessInfo> {
+        proc::read_info(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Everything known about the process: cpu times, memory, I/O, files, limits, scheduling,
+    /// sockets, environment, cgroups... Fields that need more privileges are left empty.
+    pub fn full_info(&self) -> Result<sp::FullProcessInfo> {
+        proc::read_all_info(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Every field of `/proc/PID/stat`.
+    pub fn stat(&self) -> Result<sp::Stat> {
+        stat::read_stat(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Memory mappings of the process, with their sizes when `/proc/PID/smaps` is readable.
+    pub fn maps(&self) -> Result<Vec<sp::MapEntry>> {
+        mem::read_maps(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Threads of the process (name, state, cpu time, last cpu, context switches), read from
+    /// `/proc/PID/task`.
+    pub fn threads(&self) -> Result<Vec<sp::ThreadInfo>> {
+        proc::read_threads(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Storage I/O of the process (bytes read and written, syscalls), read from `/proc/PID/io`.
+    /// Reading it needs the same privileges as tracing the process.
+    pub fn io(&self) -> Result<sp::IoCounters> {
+        proc::read_io(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Real, effective, saved and filesystem uids and gids and supplementary groups of the process,
+    /// with the names of `/etc/passwd` and `/etc/group`.
+    pub fn credentials(&self) -> Result<sp::Credentials> {
+        users::read_credentials(self.pid).map_err(|e| Error::from_io(self.pid, e))
+    }
+
+    /// Cpu time (user + system) consumed by the process, in clock ticks.
+    pub fn cpu_ticks(&self) -> Result<u64> {
+        let stat = self.stat()?;
+        Ok(stat.utime + stat.stime)
+    }
+}
+
+/// Iterator over the running processes, returned by [`processes`].
+pub struct Processes {
+    entries: fs::ReadDir,
+}
+
+impl Iterator for Processes {
+    type Item = Process;
+
+    fn next(&mut self) -> Option<Process> {
+        for entry in self.entries.by_ref() {
+            let Ok(entry) = entry else { continue };
+            if let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+                return Some(Process { pid });
+            }
+        }
+        None
+    }
+}
+
+/// Lists the running processes (the numerical entries of [`procfs::root`]), in directory order.
+///
+/// The processes are not read yet: one that exits before being read gives [`Error::NotFound`].
+pub fn processes() -> Result<Processes> {
+    Ok(Processes { entries: fs::read_dir(procfs::root())? })
+}
+
+/// Computes the cpu usage of processes from successive readings of their cpu time.
+///
+/// ```no_run
+/// use ppsx::{processes, CpuSampler};
+/// use std::{thread, time::Duration};
+///
+/// let mut sampler = CpuSampler::new();
+/// for _ in 0..2 {
+///     for process in processes()? {
+///         if let Ok(Some(cpu)) = sampler.sample(&process) {
+///             println!("{} {:.2}%", process.pid(), cpu);
+///         }
+///     }
+///     thread::sleep(Duration::from_secs(1));
+/// }
+/// # Ok::<(), ppsx::Error>(())
+/// ```
+#[derive(Default)]
+pub struct CpuSampler {
+    last: HashMap<usize, (u64, Instant)>, // last cpu ticks read for each pid and when
+}
+
+impl CpuSampler {
+    /// Creates a sampler without any reading.
+    pub fn new() -> CpuSampler {
+        CpuSampler::default()
+    }
+
+    /// Reads the cpu time of `process` and returns its usage (in percent of one cpu) since the
+    /// previous sample of the same pid, or `None` for its first sample.
+    pub fn sample(&mut self, process: &Process) -> Result<Option<f64>> {
+        let ticks = process.cpu_ticks()?;
+        Ok(self.sample_ticks(process.pid, ticks))
+    }
+
+    /// Records a cpu time read elsewhere (ex: [`sp::ThreadInfo::cpu_ticks`] for a thread) and
+    /// returns the usage of `id` since its previous sample, or `None` for its first sample.
+    pub fn sample_ticks(&mut self, id: usize, ticks: u64) -> Option<f64> {
+        let now = Instant::now();
+        let usage = self.last.get(&id).map(|(prev, time)| {
+            let delta_t = now.duration_since(*time).as_secs_f64();
+            if delta_t <= 0.0 { 0.0 } else { ticks.saturating_sub(*prev) as f64 / stat::clock_ticks() / delta_t * 100.0 }
+        });
+        self.last.insert(id, (ticks, now));
+        usage
+    }
+
+    /// Forgets the pids for which `keep` returns false (exited processes...), so that a reused
+    /// pid starts again without usage.
+    pub fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        self.last.retain(|pid, _| keep(*pid));
+    }
+
+    /// Pids sampled at least once and not forgotten.
+    pub fn pids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.last.keys().copied()
+    }
+}
@@ -0,0 +1,64 @@
+/*
+----------------------------------------------------------------------------------------
+File used to locate the procfs mount every reader works on. It is "/proc" by default but
+can be replaced once at startup (--proc-root) by another directory, such as the /proc of
+a host bind-mounted in a container or a captured copy of a /proc tree.
+----------------------------------------------------------------------------------------
+*/
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function set_root:  -input:         the directory to use as procfs root
+                    -output:        /
+                    -description:   replace "/proc" by the given directory; only the first call has an effect
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn set_root(root: PathBuf) {
+    let _ = ROOT.set(root);
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function root:  -input:         /
+                -output:        the procfs root currently in use ("/proc" if never set)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn root() -> &'static Path {
+    ROOT.get_or_init(|| PathBuf::from("/proc"))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function is_live:   -input:         /
+                    -output:        true if the procfs root is "/proc", i.e. the pids are the ones of the running kernel;
+                                    syscalls acting on a pid (kill, setpriority, ioprio_get...) are only meaningful then
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn is_live() -> bool {
+    root() == Path::new("/proc")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function path:  -input:         a path relative to the procfs root (ex: "uptime", "net/tcp")
+                -output:        the full path under the procfs root
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn path(rel: &str) -> PathBuf {
+    root().join(rel)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function pid_path:  -input:         a process id and a path relative to its directory (ex: "stat", "fd")
+                    -output:        the full path of /proc/{PID}/{rel} under the procfs root
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn pid_path(pid: usize, rel: &str) -> PathBuf {
+    root().join(pid.to_string()).join(rel)
+}
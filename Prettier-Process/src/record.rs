@@ -0,0 +1,271 @@
+/*
+----------------------------------------------------------------------------------------
+File used to record the samples of live to disk (one JSON object per line, with optional
+rotation by size or age) and to replay such a recording later with the same text view.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::live::{add_to_summary, catch_interrupt, print_samples, print_summary, wait, wait_line, MAX_DURATION};
+use crate::output::OutputOptions;
+use chrono::{NaiveDateTime, NaiveTime};
+use clap::Args;
+use ppsx::struct_proc as sp;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const MIN_SPEED: f64 = 0.001; // slowest replay accepted by --speed (0 aside)
+const PARSE_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f"; // TIMESTAMP_FORMAT of live, any number of decimals
+
+type Tick = (NaiveDateTime, Vec<sp::LiveSample>); // samples sharing the same timestamp
+
+#[derive(Args)]
+pub struct ReplayOptions {
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>, // recording and its rotated parts, in any order
+    #[arg(long, default_value = "1", value_parser = parse_speed)]
+    pub speed: f64, // replay speed factor (ex: 10 = ten times faster, 0 = no waiting)
+    #[arg(long)]
+    pub from: Option<String>, // seek: skip the ticks before this time ("YYYY-MM-DD HH:MM:SS" or "HH:MM:SS")
+    #[arg(long)]
+    pub to: Option<String>, // stop after this time
+    #[arg(long)]
+    pub step: bool, // wait for Enter between two ticks
+} // Options of the replay command
+
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+    size: u64, // bytes in the current file
+    opened: Instant,
+    part: usize, // number of the last rotated part
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+} // Appends the samples of live to a file
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_size:    -input:         a size as a number followed by an optional unit (K, M or G, powers of 1024;
+                                        bytes by default), ex: "512K", "10M"
+                        -output:        the size in bytes; an Error message if invalid
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: f64 = value.parse().map_err(|_| format!("invalid size: {}", text))?;
+    let factor = match unit.trim_end_matches(['B', 'b']) {
+        "" => 1.0,
+        "K" | "k" => 1024.0,
+        "M" | "m" => 1024.0 * 1024.0,
+        "G" | "g" => 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("invalid size unit: {} (expected K, M or G)", unit)),
+    };
+    Ok((value * factor) as u64)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_speed:   -input:         a replay speed factor, ex: "10", "0.5"
+                        -output:        the factor: 0 (no waiting) or at least MIN_SPEED; an Error message else
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_speed(text: &str) -> Result<f64, String> {
+    let speed: f64 = text.trim().parse().map_err(|_| format!("invalid speed: {}", text))?;
+    if !speed.is_finite() || speed < 0.0 || (speed > 0.0 && speed < MIN_SPEED) {
+        return Err(format!("speed must be 0 or a finite number of at least {}, got {}", MIN_SPEED, text));
+    }
+    Ok(speed)
+}
+
+impl Recorder {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method open:    -input:         the recording path and the optional rotation limits (size in bytes, age)
+                    -output:        a Recorder appending to the file; an Error if it cannot be opened
+                    -description:   rotated parts are named {path}.1, {path}.2... (oldest first); numbering goes on
+                                    after the parts left by a previous session
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn open(path: PathBuf, max_size: Option<u64>, max_age: Option<Duration>) -> Result<Recorder, io::Error> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        let prefix = format!("{}.", path.file_name().and_then(|n| n.to_str()).unwrap_or_default());
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let part = fs::read_dir(dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str().and_then(|n| n.strip_prefix(&prefix)).and_then(|n| n.parse().ok()))
+            .max()
+            .unwrap_or(0);
+        Ok(Recorder { path, file, size, opened: Instant::now(), part, max_size, max_age })
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method rotate:  -input:         /
+                    -output:        Result type (did it succed or not)
+                    -description:   rename the current file to the next part number and start a new empty file
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn rotate(&mut self) -> Result<(), io::Error> {
+        self.file.flush()?;
+        self.part += 1;
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(format!(".{}", self.part));
+        fs::rename(&self.path, rotated)?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        self.opened = Instant::now();
+        Ok(())
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method write:   -input:         the samples of one tick
+                    -output:        Result type (did it succed or not)
+                    -description:   rotate first if the current file is too big or too old (so that a tick is never
+                                    split between two files), then append one JSON line per sample
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    pub fn write(&mut self, samples: &[sp::LiveSample]) -> Result<(), Box<dyn std::error::Error>> {
+        let full = self.max_size.is_some_and(|max| self.size >= max);
+        let old = self.max_age.is_some_and(|max| self.opened.elapsed() >= max);
+        if self.size > 0 && (full || old) {
+            self.rotate()?;
+        }
+        let mut lines = String::new();
+        for sample in samples {
+            lines.push_str(&serde_json::to_string(sample)?);
+            lines.push('\n');
+        }
+        self.file.write_all(lines.as_bytes())?;
+        self.file.flush()?;
+        self.size += lines.len() as u64;
+        Ok(())
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_time:    -input:         a time given on the command line and the date of the first tick
+                        -output:        the matching date and time ("HH:MM:SS" is taken on the day of the first tick);
+                                        an Error message if invalid
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn parse_time(text: &str, first: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(text, PARSE_FORMAT) {
+        return Ok(time);
+    }
+    NaiveTime::parse_from_str(text, "%H:%M:%S%.f")
+        .map(|time| first.date().and_time(time))
+        .map_err(|_| format!("invalid time: {} (expected \"YYYY-MM-DD HH:MM:SS\" or \"HH:MM:SS\")", text))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function load_ticks:    -input:         the files of a recording
+                        -output:        the recorded samples grouped by tick, oldest first; an Error if a file is not
+                                        readable
+                        -description:   lines that are not samples (truncated last line...) are skipped
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn load_ticks(files: &[PathBuf]) -> Result<Vec<Tick>, Box<dyn std::error::Error>> {
+    let mut ticks: BTreeMap<NaiveDateTime, Vec<sp::LiveSample>> = BTreeMap::new();
+    for path in files {
+        let file = File::open(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        for line in BufReader::new(file).lines() {
+            let Ok(sample) = serde_json::from_str::<sp::LiveSample>(&line?) else { continue };
+            let Ok(time) = NaiveDateTime::parse_from_str(&sample.timestamp, PARSE_FORMAT) else { continue };
+            ticks.entry(time).or_default().push(sample);
+        }
+    }
+    Ok(ticks.into_iter().collect())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function replay:    -input:         the replay options and the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   render every recorded tick between --from and --to as live does, waiting the
+                                    recorded time between two ticks divided by --speed (or Enter with --step), then
+                                    print the summary of the replayed part; Ctrl+C stops the replay
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn replay(opts: ReplayOptions, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let ticks = load_ticks(&opts.files)?;
+    let Some((first, _)) = ticks.first() else {
+        println!("===== No sample recorded =====");
+        return Ok(());
+    };
+    let from = opts.from.as_deref().map(|t| parse_time(t, *first)).transpose()?;
+    let to = opts.to.as_deref().map(|t| parse_time(t, *first)).transpose()?;
+
+    let mut stream = out.stream()?;
+    stream.text("===== Replay =====\n")?;
+    catch_interrupt();
+    let mut summary: BTreeMap<usize, sp::LiveSummary> = BTreeMap::new();
+    let mut previous: Option<NaiveDateTime> = None;
+    for (time, samples) in ticks {
+        if from.is_some_and(|from| time < from) {
+            continue;
+        }
+        if to.is_some_and(|to| time > to) {
+            break;
+        }
+        if opts.step {
+            if previous.is_some() && !wait_line()? {
+                break; // Ctrl+C was pressed while waiting for Enter
+            }
+        } else if let Some(previous) = previous
+            && opts.speed > 0.0
+            && let Ok(gap) = (time - previous).to_std()
+            && !wait(Duration::try_from_secs_f64(gap.as_secs_f64() / opts.speed).map_or(MAX_DURATION, |d| d.min(MAX_DURATION))) {
+            break;
+        }
+        previous = Some(time);
+        add_to_summary(&mut summary, &samples);
+        print_samples(&mut stream, &samples, &[])?;
+    }
+    print_summary(&mut stream, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512K"), Ok(512 * 1024));
+        assert_eq!(parse_size("10M"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("1.5G"), Ok(3 * 512 * 1024 * 1024));
+        assert_eq!(parse_size(" 2kB "), Ok(2048));
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("10T").is_err());
+        assert!(parse_size("1.2.3K").is_err());
+    }
+
+    #[test]
+    fn speeds() {
+        assert_eq!(parse_speed("1"), Ok(1.0));
+        assert_eq!(parse_speed("0"), Ok(0.0));
+        assert_eq!(parse_speed("0.5"), Ok(0.5));
+        assert_eq!(parse_speed("1e6"), Ok(1e6));
+        assert!(parse_speed("1e-300").is_err());
+        assert!(parse_speed("-2").is_err());
+        assert!(parse_speed("inf").is_err());
+        assert!(parse_speed("NaN").is_err());
+        assert!(parse_speed("fast").is_err());
+    }
+}
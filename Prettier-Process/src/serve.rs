@@ -0,0 +1,454 @@
+/*
+----------------------------------------------------------------------------------------
+File used to serve the figures of ppsx over HTTP in the Prometheus text format (or in
+OpenMetrics when the scraper asks for it): per-process cpu, memory, threads, fds and I/O,
+plus the system figures of stats. Every scrape reads /proc again.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::stats;
+use clap::Args;
+use ppsx::procfs;
+use ppsx::stat::clock_ticks;
+use ppsx::struct_proc as sp;
+use ppsx::processes;
+use ppsx::users::{read_users, resolve_uid};
+use regex::Regex;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PROMETHEUS_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+const OPENMETRICS_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const WORKERS: usize = 4; // scrapes answered at the same time, the next connections wait in the listen backlog
+const TIMEOUT: Duration = Duration::from_secs(5); // longest wait for a scraper to send its request or read the answer
+const INDEX: &str = "<html><head><title>ppsx</title></head><body><h1>ppsx</h1><p><a href=\"/metrics\">Metrics</a></p></body></html>\n";
+
+#[derive(Args)]
+pub struct ServeOptions {
+    #[arg(long, default_value = "127.0.0.1:9256")]
+    pub listen: String, // address and port to listen on
+    #[arg(long)]
+    pub include_name: Option<String>, // regex: only export the processes whose name matches
+    #[arg(long)]
+    pub exclude_name: Option<String>, // regex: never export the processes whose name matches
+    #[arg(long, value_delimiter = ',')]
+    pub include_user: Vec<String>, // user names or uids: only export their processes
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_user: Vec<String>, // user names or uids: never export their processes
+    #[arg(long, value_delimiter = ',')]
+    pub include_pid: Vec<usize>, // only export these processes
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_pid: Vec<usize>, // never export these processes
+} // Options of the serve command
+
+struct Selection {
+    include_name: Option<Regex>,
+    exclude_name: Option<Regex>,
+    include_uids: Vec<u32>,
+    exclude_uids: Vec<u32>,
+    include_pids: Vec<usize>,
+    exclude_pids: Vec<usize>,
+} // Compiled filters deciding which processes get per-process series
+
+struct ProcessMetrics {
+    labels: [(&'static str, String); 3], // pid, name, user
+    cpu_seconds: f64,
+    rss_bytes: f64,
+    virtual_bytes: f64,
+    threads: f64,
+    fds: Option<f64>,
+    io: Option<sp::IoCounters>,
+} // Figures of one exported process
+
+struct Exposition {
+    output: String,
+    openmetrics: bool,
+} // Metrics page being written
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve_uids:  -input:         user names or uids given on the command line
+                        -output:        the matching uids; an Error message naming the first unknown user
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn resolve_uids(users: &[String]) -> Result<Vec<u32>, String> {
+    users.iter().map(|user| resolve_uid(user).ok_or_else(|| format!("unknown user: {}", user))).collect()
+}
+
+impl Selection {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method new:     -input:         the serve options
+                    -output:        the compiled Selection; an Error if a regex or a user is invalid
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn new(opts: &ServeOptions) -> Result<Selection, Box<dyn std::error::Error>> {
+        Ok(Selection {
+            include_name: opts.include_name.as_deref().map(Regex::new).transpose()?,
+            exclude_name: opts.exclude_name.as_deref().map(Regex::new).transpose()?,
+            include_uids: resolve_uids(&opts.include_user)?,
+            exclude_uids: resolve_uids(&opts.exclude_user)?,
+            include_pids: opts.include_pid.clone(),
+            exclude_pids: opts.exclude_pid.clone(),
+        })
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method matches: -input:         a ProcessInfo
+                    -output:        true if the process passes the include filters and none of the exclude filters
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn matches(&self, info: &sp::ProcessInfo) -> bool {
+        self.include_name.as_ref().is_none_or(|re| re.is_match(&info.name))
+            && !self.exclude_name.as_ref().is_some_and(|re| re.is_match(&info.name))
+            && (self.include_uids.is_empty() || self.include_uids.contains(&info.uid))
+            && !self.exclude_uids.contains(&info.uid)
+            && (self.include_pids.is_empty() || self.include_pids.contains(&info.pid))
+            && !self.exclude_pids.contains(&info.pid)
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_processes: -input:        the Selection
+                         -output:       the figures of every selected process (sorted by pid); an Error if the procfs
+                                        root is not readable
+                         -description:  fds and I/O are left out for the processes we are not allowed to inspect
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_processes(selection: &Selection) -> Result<Vec<ProcessMetrics>, ppsx::Error> {
+    let users = read_users();
+    let tick = clock_ticks();
+    let mut procs: Vec<(usize, ProcessMetrics)> = Vec::new();
+    for process in processes()? {
+        let Ok(info) = process.info() else { continue };
+        if !selection.matches(&info) {
+            continue;
+        }
+        let Ok(stat) = process.stat() else { continue };
+        let user = users.get(&info.uid).cloned().unwrap_or_else(|| info.uid.to_string());
+        procs.push((info.pid, ProcessMetrics {
+            labels: [("pid", info.pid.to_string()), ("name", info.name), ("user", user)],
+            cpu_seconds: (stat.utime + stat.stime) as f64 / tick,
+            rss_bytes: info.vm_rss as f64 * 1024.0,
+            virtual_bytes: info.vm_size as f64 * 1024.0,
+            threads: info.threads as f64,
+            fds: fs::read_dir(procfs::pid_path(info.pid, "fd")).ok().map(|fds| fds.count() as f64),
+            io: process.io().ok(),
+        }));
+    }
+    procs.sort_by_key(|(pid, _)| *pid);
+    Ok(procs.into_iter().map(|(_, p)| p).collect())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function escape:    -input:         a label value
+                    -output:        the value with backslashes, double quotes and line breaks escaped
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl Exposition {
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method family:  -input:         the name of a metric, its type (counter or gauge) and its description
+                    -output:        /
+                    -description:   write the HELP and TYPE lines of the metric; OpenMetrics names a counter family
+                                    without its "_total" suffix
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let name = if self.openmetrics && kind == "counter" { name.trim_end_matches("_total") } else { name };
+        self.output.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method sample:  -input:         the name of a metric, its labels and its value
+                    -output:        /
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.output.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{}=\"{}\"", k, escape(v))).collect();
+            self.output.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.output.push_str(&format!(" {}\n", value));
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method single:  -input:         the name of a metric without labels, its type, its description and its value
+                    -output:        /
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn single(&mut self, name: &str, kind: &str, help: &str, value: f64) {
+        self.family(name, kind, help);
+        self.sample(name, &[], value);
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method processes:   -input:         the exported processes
+                        -output:        /
+                        -description:   write one family per figure, with one sample per process that has it
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn processes(&mut self, procs: &[ProcessMetrics]) {
+        type Figure = fn(&ProcessMetrics) -> Option<f64>;
+        let figures: [(&str, &str, &str, Figure); 7] = [
+            ("ppsx_process_cpu_seconds_total", "counter", "Cpu time (user + system) consumed by the process.", |p| Some(p.cpu_seconds)),
+            ("ppsx_process_resident_memory_bytes", "gauge", "Resident memory of the process.", |p| Some(p.rss_bytes)),
+            ("ppsx_process_virtual_memory_bytes", "gauge", "Virtual memory of the process.", |p| Some(p.virtual_bytes)),
+            ("ppsx_process_threads", "gauge", "Threads of the process.", |p| Some(p.threads)),
+            ("ppsx_process_open_fds", "gauge", "Open file descriptors of the process.", |p| p.fds),
+            ("ppsx_process_read_bytes_total", "counter", "Bytes read from storage by the process.", |p| p.io.as_ref().map(|io| io.read_bytes as f64)),
+            ("ppsx_process_write_bytes_total", "counter", "Bytes written to storage by the process.", |p| p.io.as_ref().map(|io| io.write_bytes as f64)),
+        ];
+        for (name, kind, help, figure) in figures {
+            self.family(name, kind, help);
+            for p in procs {
+                if let Some(value) = figure(p) {
+                    let labels: Vec<(&str, &str)> = p.labels.iter().map(|(k, v)| (*k, v.as_str())).collect();
+                    self.sample(name, &labels, value);
+                }
+            }
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method system:  -input:         the Statistics of the system
+                    -output:        /
+                    -description:   write the figures of the stats command (memory in bytes)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn system(&mut self, stats: &sp::Statistics) {
+        self.family("ppsx_processes", "gauge", "Processes by state.");
+        for (state, count) in [("running", stats.running), ("sleeping", stats.sleeping), ("disk_sleeping", stats.disk_sleeping),
+            ("zombie", stats.zombie), ("stopped", stats.stopped), ("idle", stats.idle)] {
+            self.sample("ppsx_processes", &[("state", state)], count as f64);
+        }
+        self.single("ppsx_memory_pss_bytes", "gauge", "Sum of the proportional set size of the accounted processes.", stats.pss_memory_kb as f64 * 1024.0);
+        self.single("ppsx_memory_uss_bytes", "gauge", "Sum of the unique set size of the accounted processes.", stats.uss_memory_kb as f64 * 1024.0);
+        self.single("ppsx_memory_swap_pss_bytes", "gauge", "Sum of the proportional swap of the accounted processes.", stats.swap_pss_kb as f64 * 1024.0);
+        self.single("ppsx_memory_accounted_processes", "gauge", "Processes whose smaps_rollup was readable.", stats.memory_accounted_processes as f64);
+        self.single("ppsx_memory_rss_bytes", "gauge", "Sum of the resident memory of every process (shared pages counted once per process).", stats.rss_memory_kb as f64 * 1024.0);
+        self.single("ppsx_memory_virtual_bytes", "gauge", "Sum of the virtual memory of every process.", stats.virtual_memory_kb as f64 * 1024.0);
+
+        let m = &stats.meminfo;
+        self.family("ppsx_meminfo_bytes", "gauge", "System memory from /proc/meminfo.");
+        for (field, kb) in [("mem_total", m.mem_total), ("mem_free", m.mem_free), ("mem_available", m.mem_available), ("buffers", m.buffers),
+            ("cached", m.cached), ("shmem", m.shmem), ("swap_total", m.swap_total), ("swap_free", m.swap_free), ("swap_cached", m.swap_cached),
+            ("anon_hugepages", m.anon_hugepages), ("hugepage_size", m.hugepage_size)] {
+            self.sample("ppsx_meminfo_bytes", &[("field", field)], kb as f64 * 1024.0);
+        }
+        self.family("ppsx_meminfo_hugepages", "gauge", "Huge pages from /proc/meminfo.");
+        self.sample("ppsx_meminfo_hugepages", &[("state", "total")], m.hugepages_total as f64);
+        self.sample("ppsx_meminfo_hugepages", &[("state", "free")], m.hugepages_free as f64);
+
+        let a = &stats.system;
+        self.single("ppsx_uptime_seconds", "gauge", "Time since boot.", a.uptime_seconds);
+        self.family("ppsx_load_average", "gauge", "Load average.");
+        for (period, load) in ["1m", "5m", "15m"].into_iter().zip(a.load_average) {
+            self.sample("ppsx_load_average", &[("period", period)], load);
+        }
+        self.single("ppsx_context_switches_total", "counter", "Context switches since boot.", a.context_switches as f64);
+        self.single("ppsx_forks_total", "counter", "Processes and threads created since boot.", a.forks as f64);
+        self.single("ppsx_procs_running", "gauge", "Runnable threads.", a.procs_running as f64);
+        self.single("ppsx_procs_blocked", "gauge", "Threads blocked on I/O.", a.procs_blocked as f64);
+        self.family("ppsx_cpu_usage_percent", "gauge", "Share of time spent by a cpu in each mode during the scrape.");
+        for cpu in std::iter::once(&a.cpu_total).chain(a.cpu_cores.iter()) {
+            let name = if cpu.cpu == "cpu" { "all" } else { cpu.cpu.as_str() };
+            for (mode, value) in [("user", cpu.user), ("system", cpu.system), ("iowait", cpu.iowait), ("steal", cpu.steal), ("idle", cpu.idle)] {
+                self.sample("ppsx_cpu_usage_percent", &[("cpu", name), ("mode", mode)], value);
+            }
+        }
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render:    -input:         the Selection and whether the scraper asked for OpenMetrics
+                    -output:        the metrics page; an Error if the system figures are not readable
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render(selection: &Selection, openmetrics: bool) -> Result<String, Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let mut page = Exposition { output: String::new(), openmetrics };
+    page.processes(&read_processes(selection)?);
+    page.system(&stats::collect()?);
+    page.single("ppsx_scrape_duration_seconds", "gauge", "Time taken to read the figures.", start.elapsed().as_secs_f64());
+    if openmetrics {
+        page.output.push_str("# EOF\n");
+    }
+    Ok(page.output)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function respond:   -input:         a connection and the Selection
+                    -output:        Result type (did it succed or not)
+                    -description:   read the request line and headers, then answer GET (or HEAD) /metrics with the
+                                    metrics, / with a link to them and anything else with an error status
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn respond(mut stream: TcpStream, selection: &Selection) -> Result<(), Box<dyn std::error::Error>> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    let mut openmetrics = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("accept") {
+            openmetrics = value.contains("application/openmetrics-text");
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => match render(selection, openmetrics) {
+            Ok(page) => ("200 OK", if openmetrics { OPENMETRICS_TYPE } else { PROMETHEUS_TYPE }, page),
+            Err(e) => ("500 Internal Server Error", "text/plain; charset=utf-8", format!("{}\n", e)),
+        },
+        ("GET" | "HEAD", "/") => ("200 OK", "text/html; charset=utf-8", String::from(INDEX)),
+        ("GET" | "HEAD", _) => ("404 Not Found", "text/plain; charset=utf-8", String::from("Not found\n")),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", String::from("Method not allowed\n")),
+    };
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, content_type, body.len());
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    stream.write_all(response.as_bytes())?;
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function work:      -input:         a bound listener and the Selection
+                    -output:        /
+                    -description:   answer the connections of the listener one at a time; a failed connection is
+                                    reported without stopping the worker
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn work(listener: TcpListener, selection: &Selection) {
+    for stream in listener.incoming() {
+        let result = stream.map_err(|e| e.into()).and_then(|stream| respond(stream, selection));
+        if let Err(e) = result {
+            eprintln!("===== Request failed: {} =====", e);
+        }
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function accept:    -input:         a bound listener and the Selection
+                    -output:        Result type (did it succed or not)
+                    -description:   run WORKERS threads accepting on clones of the listener, so that a slow scraper
+                                    or a scrape (which reads /proc twice, 250ms apart) does not hold the next ones,
+                                    while a burst of connections never sweeps /proc more than WORKERS times at once
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn accept(listener: TcpListener, selection: Arc<Selection>) -> Result<(), io::Error> {
+    let mut workers = Vec::new();
+    for _ in 0..WORKERS {
+        let listener = listener.try_clone()?;
+        let selection = Arc::clone(&selection);
+        workers.push(thread::spawn(move || work(listener, &selection)));
+    }
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function serve:     -input:         the serve options
+                    -output:        Result type (did it succed or not)
+                    -description:   listen on the given address and answer the scrapers until the program is stopped
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn serve(opts: ServeOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let selection = Selection::new(&opts)?;
+    let listener = TcpListener::bind(&opts.listen).map_err(|e| format!("cannot listen on {}: {}", opts.listen, e))?;
+    println!("===== Serving metrics on http://{}/metrics =====", listener.local_addr()?);
+    Ok(accept(listener, Arc::new(selection))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    // serve the selection on a free port and scrape /metrics once, with the given Accept header
+    fn scrape(selection: Selection, accept_header: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || accept(listener, Arc::new(selection)));
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET /metrics HTTP/1.1\r\nHost: {}\r\nAccept: {}\r\n\r\n", address, accept_header).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    // selection keeping (or dropping) the given processes
+    fn by_pid(include: &[usize], exclude: &[usize]) -> Selection {
+        Selection {
+            include_name: None,
+            exclude_name: None,
+            include_uids: Vec::new(),
+            exclude_uids: Vec::new(),
+            include_pids: include.to_vec(),
+            exclude_pids: exclude.to_vec(),
+        }
+    }
+
+    // the test process and its pid label
+    fn own_pid() -> (usize, String) {
+        let pid = std::process::id() as usize;
+        (pid, format!("pid=\"{}\"", pid))
+    }
+
+    #[test]
+    fn prometheus_scrape() {
+        let (pid, label) = own_pid();
+        let response = scrape(by_pid(&[pid], &[]), "*/*");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(&format!("Content-Type: {}\r\n", PROMETHEUS_TYPE)));
+        assert!(!response.contains("# EOF"));
+        let threads: Vec<&str> = response.lines().filter(|l| l.starts_with("ppsx_process_threads{")).collect();
+        assert_eq!(threads.len(), 1);
+        assert!(threads[0].contains(&label));
+        assert!(response.contains("ppsx_processes{state=\"running\"}"));
+    }
+
+    #[test]
+    fn openmetrics_scrape() {
+        let (pid, label) = own_pid();
+        let response = scrape(by_pid(&[], &[pid]), "application/openmetrics-text; version=1.0.0");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(&format!("Content-Type: {}\r\n", OPENMETRICS_TYPE)));
+        assert!(response.ends_with("# EOF\n"));
+        assert!(response.contains("# TYPE ppsx_process_cpu_seconds counter"));
+        assert!(!response.contains(&label));
+    }
+}
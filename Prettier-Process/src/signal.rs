@@ -0,0 +1,197 @@
+/*
+----------------------------------------------------------------------------------------
+File used to translate signal names (as written by the user) to their numbers and back,
+and to send signals to the processes selected by the signal command.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::filter::{descendants, Filters};
+use ppsx::proc::{get_all_info, read_info};
+use ppsx::procfs;
+use ppsx::struct_proc as sp;
+use clap::Args;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", libc::SIGHUP), ("INT", libc::SIGINT), ("QUIT", libc::SIGQUIT), ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP), ("ABRT", libc::SIGABRT), ("BUS", libc::SIGBUS), ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL), ("USR1", libc::SIGUSR1), ("SEGV", libc::SIGSEGV), ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE), ("ALRM", libc::SIGALRM), ("TERM", libc::SIGTERM), ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT), ("STOP", libc::SIGSTOP), ("TSTP", libc::SIGTSTP), ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU), ("URG", libc::SIGURG), ("XCPU", libc::SIGXCPU), ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM), ("PROF", libc::SIGPROF), ("WINCH", libc::SIGWINCH), ("IO", libc::SIGIO),
+    ("PWR", libc::SIGPWR), ("SYS", libc::SIGSYS),
+]; // Standard signals of Linux, see signal(7)
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_signal:  -input:         a signal as a number or a name, with or without "SIG" (ex: "9", "KILL", "sigterm")
+                        -output:        the number of the signal; None if unknown
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_signal(signal: &str) -> Option<i32> {
+    if let Ok(number) = signal.parse::<i32>() {
+        return if (0..=libc::SIGRTMAX()).contains(&number) { Some(number) } else { None };
+    }
+    let upper = signal.trim().to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS.iter().find(|(n, _)| *n == name).map(|(_, number)| *number)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function signal_name:   -input:         a signal number
+                        -output:        its name (ex: "SIGTERM"), or the number itself if it has no name
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn signal_name(signal: i32) -> String {
+    match SIGNALS.iter().find(|(_, number)| *number == signal) {
+        Some((name, _)) => format!("SIG{}", name),
+        None => signal.to_string(),
+    }
+}
+
+#[derive(Args)]
+pub struct SignalOptions {
+    pub signal: String, // number or name, with or without "SIG"
+    pub pids: Vec<usize>,
+    #[command(flatten)]
+    pub filters: Filters,
+    #[arg(long)]
+    pub tree: Option<usize>, // this pid and all its descendants
+    #[arg(long)]
+    pub dry_run: bool, // only show the processes that would be signaled
+    #[arg(long, default_value_t = 5)]
+    pub confirm_above: usize, // ask for a confirmation when more processes match
+    #[arg(long)]
+    pub yes: bool, // never ask for a confirmation
+    #[arg(long)]
+    pub force: bool, // allow signaling PID 1, ppsx itself and its ancestors
+} // Options of the signal command
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function protected_pids:    -input:         /
+                            -output:        the pids that are never signaled without --force (never from top), with the
+                                            reason: PID 1, ppsx itself and every ancestor of ppsx (the shell running it...)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn protected_pids() -> HashMap<usize, &'static str> {
+    let mut protected = HashMap::new();
+    protected.insert(1, "init");
+    let me = std::process::id() as usize;
+    protected.insert(me, "ppsx itself");
+    let mut pid = me;
+    while let Ok(info) = read_info(pid) {
+        if info.ppid == 0 || info.ppid == pid {
+            break;
+        }
+        pid = info.ppid;
+        protected.entry(pid).or_insert("ancestor of ppsx");
+    }
+    protected
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve_targets:   -input:         the signal options
+                            -output:        the processes to signal, sorted by pid
+                            -description:   the explicit pids and the --tree descendants are the candidates (every
+                                            process if none of them is given), then the list filters (--user, --name,
+                                            --state, --min-rss) keep the matching ones, exactly as list does
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn resolve_targets(opts: &SignalOptions) -> Result<Vec<sp::ProcessInfo>, Box<dyn std::error::Error>> {
+    let procs = get_all_info()?;
+    let mut wanted: HashSet<usize> = opts.pids.iter().copied().collect();
+    if let Some(root) = opts.tree {
+        wanted.extend(descendants(&procs, root));
+    }
+    let explicit = !opts.pids.is_empty() || opts.tree.is_some();
+    let candidates = procs.into_iter().filter(|p| !explicit || wanted.contains(&p.pid)).collect();
+    let mut targets = opts.filters.select(candidates)?;
+    targets.sort_by_key(|p| p.pid);
+    Ok(targets)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function confirm:   -input:         the question to ask
+                    -output:        true if the user answered yes on stdin
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function send:  -input:         the signal options
+                -output:        Result type (did it succed or not)
+                -description:   resolve the targets, drop the protected ones (unless --force), then show them
+                                (--dry-run), refuse to go further if the procfs root is not the live /proc, ask
+                                for a confirmation if more than --confirm-above match and finally send the signal
+                                to each of them with kill(2)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn send(opts: SignalOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let sig = parse_signal(&opts.signal).ok_or_else(|| format!("unknown signal: {}", opts.signal))?;
+    if opts.pids.is_empty() && opts.tree.is_none() && opts.filters.is_empty() {
+        return Err("no target: give PIDs, --tree or a filter (--name, --user...)".into());
+    }
+
+    let mut targets = resolve_targets(&opts)?;
+    if !opts.force {
+        let protected = protected_pids();
+        targets.retain(|p| match protected.get(&p.pid) {
+            Some(reason) => {
+                println!("Refusing to signal PID {} ({}: {}), use --force to override", p.pid, p.name, reason);
+                false
+            }
+            None => true,
+        });
+    }
+    if targets.is_empty() {
+        println!("===== No matching process =====");
+        return Ok(());
+    }
+
+    let name = signal_name(sig);
+    if opts.dry_run {
+        println!("===== Dry run: {} would be sent to {} process(es) =====", name, targets.len());
+        for p in &targets {
+            println!("PID: {} - {} ({})", p.pid, p.name, p.cmdline);
+        }
+        return Ok(());
+    }
+    if !procfs::is_live() {
+        return Err("cannot send signals: --proc-root is not the live /proc (use --dry-run to preview the targets)".into());
+    }
+    if targets.len() > opts.confirm_above && !opts.yes {
+        for p in &targets {
+            println!("PID: {} - {}", p.pid, p.name);
+        }
+        if !confirm(&format!("Send {} to these {} processes?", name, targets.len())) {
+            println!("===== Aborted =====");
+            return Ok(());
+        }
+    }
+
+    let mut failed = 0;
+    for p in &targets {
+        if unsafe { libc::kill(p.pid as libc::pid_t, sig) } == 0 {
+            println!("Sent {} to PID {} ({})", name, p.pid, p.name);
+        } else {
+            failed += 1;
+            println!("Cannot send {} to PID {} ({}): {}", name, p.pid, p.name, io::Error::last_os_error());
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} process(es) could not be signaled", failed, targets.len()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+/*
+----------------------------------------------------------------------------------------
+File used to capture the state of every process at a point in time and to compare two
+captures (before and after a deploy...). A process is identified by its pid AND its start
+time, so that a pid reused by another process is reported as exited + new.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::output::OutputOptions;
+use chrono::Local;
+use ppsx::procfs;
+use ppsx::stat::clock_ticks;
+use ppsx::struct_proc as sp;
+use ppsx::system::{read_boot_id, read_boot_time};
+use ppsx::processes;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function capture:   -input:         /
+                    -output:        a Snapshot of every readable process (sorted by pid); an Error if the procfs root is
+                                    not readable
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn capture() -> Result<sp::Snapshot, ppsx::Error> {
+    let mut entries: Vec<sp::SnapshotEntry> = Vec::new();
+    for process in processes()? {
+        let (Ok(info), Ok(stat)) = (process.info(), process.stat()) else { continue };
+        let fd_count = fs::read_dir(procfs::pid_path(process.pid(), "fd")).ok().map(|fds| fds.count());
+        entries.push(sp::SnapshotEntry { info, start_time: stat.starttime, fd_count });
+    }
+    entries.sort_by_key(|e| e.info.pid);
+    Ok(sp::Snapshot {
+        timestamp: Local::now().to_rfc3339(),
+        boot_id: read_boot_id(),
+        boot_time: read_boot_time(),
+        clock_ticks: clock_ticks() as u64,
+        processes: entries,
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function snapshot:  -input:         the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   capture every process and write the snapshot in the chosen format (json to be
+                                    read back by diff, ex: --output snap.json)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn snapshot(out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let snap = capture()?;
+    out.emit(&snap, || {
+        let mut output = format!("===== Snapshot =====\nTaken at: {}\nBoot ID: {}\nProcesses: {}\n", snap.timestamp, snap.boot_id, snap.processes.len());
+        output.push_str(&format!("{:>7}  {:>10}  {:>10}  {:>7}  {:>5}  {}\n", "PID", "START", "RSS(kB)", "THREADS", "FDS", "NAME"));
+        for e in &snap.processes {
+            let fds = e.fd_count.map(|c| c.to_string()).unwrap_or_else(|| String::from("-"));
+            output.push_str(&format!("{:>7}  {:>10}  {:>10}  {:>7}  {:>5}  {}\n", e.info.pid, e.start_time, e.info.vm_rss, e.info.threads, fds, e.info.name));
+        }
+        output
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function load:  -input:         the path of a snapshot file
+                -output:        the Snapshot it holds (JSON, or YAML); an Error if it is not readable or not a snapshot
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn load(path: &Path) -> Result<sp::Snapshot, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    match serde_json::from_str(&content) {
+        Ok(snap) => Ok(snap),
+        Err(json_err) => serde_yaml::from_str(&content)
+            .map_err(|_| format!("{} is not a snapshot: {}", path.display(), json_err).into()),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function compare:   -input:         two snapshots (before, after)
+                    -output:        their SnapshotDiff: processes only in one of them, command lines changed by an exec
+                                    and the rss/threads/fds changes of the processes in both (biggest rss growth first)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn compare(before: sp::Snapshot, after: sp::Snapshot) -> sp::SnapshotDiff {
+    let same_boot = before.boot_id == after.boot_id;
+    // start times only identify a process within a boot: after a reboot nothing is matched
+    let mut old: HashMap<(usize, u64), sp::SnapshotEntry> = HashMap::new();
+    let mut exited: Vec<sp::SnapshotEntry> = Vec::new();
+    if same_boot {
+        old = before.processes.into_iter().map(|e| ((e.info.pid, e.start_time), e)).collect();
+    } else {
+        exited = before.processes;
+    }
+
+    let mut diff = sp::SnapshotDiff {
+        before: before.timestamp,
+        after: after.timestamp,
+        same_boot,
+        new_processes: Vec::new(),
+        exited_processes: Vec::new(),
+        cmdline_changes: Vec::new(),
+        growth: Vec::new(),
+    };
+    for now in after.processes {
+        let Some(then) = old.remove(&(now.info.pid, now.start_time)) else {
+            diff.new_processes.push(now);
+            continue;
+        };
+        if then.info.cmdline != now.info.cmdline {
+            diff.cmdline_changes.push(sp::CmdlineChange {
+                pid: now.info.pid,
+                name: now.info.name.clone(),
+                before: then.info.cmdline.clone(),
+                after: now.info.cmdline.clone(),
+            });
+        }
+        let fd_delta = match (then.fd_count, now.fd_count) {
+            (Some(a), Some(b)) => Some(b as i64 - a as i64),
+            _ => None,
+        };
+        let rss_delta = now.info.vm_rss as i64 - then.info.vm_rss as i64;
+        let threads_delta = now.info.threads as i64 - then.info.threads as i64;
+        if rss_delta != 0 || threads_delta != 0 || fd_delta.is_some_and(|d| d != 0) {
+            diff.growth.push(sp::ProcessGrowth {
+                pid: now.info.pid,
+                name: now.info.name,
+                rss_before: then.info.vm_rss,
+                rss_after: now.info.vm_rss,
+                rss_delta,
+                threads_before: then.info.threads,
+                threads_after: now.info.threads,
+                threads_delta,
+                fd_before: then.fd_count,
+                fd_after: now.fd_count,
+                fd_delta,
+            });
+        }
+    }
+    exited.extend(old.into_values());
+    diff.exited_processes = exited;
+    diff.exited_processes.sort_by_key(|e| e.info.pid);
+    diff.growth.sort_by(|a, b| b.rss_delta.cmp(&a.rss_delta).then(a.pid.cmp(&b.pid)));
+    diff
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render_diff:   -input:         a SnapshotDiff
+                        -output:        the diff as text, one section per kind of change
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render_diff(diff: &sp::SnapshotDiff) -> String {
+    let mut output = format!("===== Diff =====\nBefore: {}\nAfter: {}\n", diff.before, diff.after);
+    if !diff.same_boot {
+        output.push_str("The system rebooted between the snapshots: every process is reported as new\n");
+    }
+    let entries = |title: &str, list: &[sp::SnapshotEntry]| {
+        let mut output = format!("\n--- {} ({}) ---\n", title, list.len());
+        for e in list {
+            output.push_str(&format!("{:>7}  {:<16} {:>10} kB  {}\n", e.info.pid, e.info.name, e.info.vm_rss, e.info.cmdline));
+        }
+        output
+    };
+    output.push_str(&entries("New processes", &diff.new_processes));
+    output.push_str(&entries("Exited processes", &diff.exited_processes));
+
+    output.push_str(&format!("\n--- Changed command lines ({}) ---\n", diff.cmdline_changes.len()));
+    for c in &diff.cmdline_changes {
+        output.push_str(&format!("{:>7}  {:<16} {}\n{:>7}  {:<16} -> {}\n", c.pid, c.name, c.before, "", "", c.after));
+    }
+
+    output.push_str(&format!("\n--- Resource changes ({}) ---\n", diff.growth.len()));
+    if !diff.growth.is_empty() {
+        output.push_str(&format!("{:>7}  {:<16} {:>10} {:>10} {:>8} {:>8} {:>6} {:>6}\n",
+            "PID", "NAME", "RSS(kB)", "DELTA", "THREADS", "DELTA", "FDS", "DELTA"));
+    }
+    for g in &diff.growth {
+        let fds = g.fd_after.map(|c| c.to_string()).unwrap_or_else(|| String::from("-"));
+        let fd_delta = g.fd_delta.map(|d| format!("{:+}", d)).unwrap_or_else(|| String::from("-"));
+        output.push_str(&format!("{:>7}  {:<16} {:>10} {:>+10} {:>8} {:>+8} {:>6} {:>6}\n",
+            g.pid, g.name, g.rss_after, g.rss_delta, g.threads_after, g.threads_delta, fds, fd_delta));
+    }
+    output
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function diff:  -input:         the paths of two snapshots (before, after) and the output options
+                -output:        Result type (did it succed or not)
+                -description:   load both snapshots, compare them and display the differences in the chosen format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn diff(before: &Path, after: &Path, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let diff = compare(load(before)?, load(after)?);
+    out.emit(&diff, || render_diff(&diff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: usize, start_time: u64, cmdline: &str, vm_rss: usize, fd_count: Option<usize>) -> sp::SnapshotEntry {
+        sp::SnapshotEntry {
+            info: sp::ProcessInfo {
+                pid,
+                name: format!("proc{}", pid),
+                state: String::from("S"),
+                ppid: 1,
+                uid: 0,
+                gid: 0,
+                euid: 0,
+                egid: 0,
+                threads: 1,
+                vm_size: 2 * vm_rss,
+                vm_rss,
+                cmdline: cmdline.to_string(),
+            },
+            start_time,
+            fd_count,
+        }
+    }
+
+    fn snapshot(boot_id: &str, processes: Vec<sp::SnapshotEntry>) -> sp::Snapshot {
+        sp::Snapshot { timestamp: String::new(), boot_id: boot_id.to_string(), boot_time: 0, clock_ticks: 100, processes }
+    }
+
+    #[test]
+    fn same_boot() {
+        let before = snapshot("a", vec![
+            entry(10, 100, "server", 1000, Some(5)),
+            entry(20, 200, "worker", 500, Some(3)),
+            entry(30, 300, "job", 100, None),
+            entry(40, 400, "idle", 50, Some(1)),
+        ]);
+        let after = snapshot("a", vec![
+            entry(10, 100, "server", 3000, Some(9)),
+            entry(20, 200, "worker --child", 400, Some(3)),
+            entry(30, 350, "job", 100, None), // pid reused by a new process
+            entry(40, 400, "idle", 50, Some(1)),
+            entry(50, 500, "new", 10, None),
+        ]);
+        let diff = compare(before, after);
+        assert!(diff.same_boot);
+        assert_eq!(diff.new_processes.iter().map(|e| e.info.pid).collect::<Vec<usize>>(), vec![30, 50]);
+        assert_eq!(diff.exited_processes.iter().map(|e| (e.info.pid, e.start_time)).collect::<Vec<_>>(), vec![(30, 300)]);
+        assert_eq!(diff.cmdline_changes.len(), 1);
+        assert_eq!(diff.cmdline_changes[0].after, "worker --child");
+        // biggest rss growth first, unchanged processes left out
+        assert_eq!(diff.growth.iter().map(|g| (g.pid, g.rss_delta)).collect::<Vec<_>>(), vec![(10, 2000), (20, -100)]);
+        assert_eq!(diff.growth[0].fd_delta, Some(4));
+    }
+
+    #[test]
+    fn after_reboot() {
+        let before = snapshot("a", vec![entry(10, 100, "server", 1000, None)]);
+        let after = snapshot("b", vec![entry(10, 100, "server", 1000, None)]);
+        let diff = compare(before, after);
+        assert!(!diff.same_boot);
+        assert_eq!(diff.new_processes.len(), 1);
+        assert_eq!(diff.exited_processes.len(), 1);
+        assert!(diff.growth.is_empty());
+    }
+}
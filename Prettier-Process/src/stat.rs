@@ -0,0 +1,182 @@
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::fs;
+use std::io;
+use libc::{sysconf, _SC_CLK_TCK};
+use std::str::FromStr;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function field: -input:         the fields following the process name and the index of the wanted one
+                -output:        the parsed field, or its default value if missing or unparsable
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn field<T: FromStr + Default>(rest: &[&str], index: usize) -> T {
+    rest.get(index).and_then(|s| s.parse().ok()).unwrap_or_default()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_stat:    -input:         the content of a /proc/{PID}/stat (or /proc/{PID}/task/{TID}/stat) file
+                        -output:        a Stat containing every field of the file; None if the content is malformed
+                        -description:   the process name (comm) is written between parenthesis and may itself contain
+                                        spaces and parenthesis, so it goes from the first '(' to the LAST ')' and the
+                                        remaining fields are split on whitespaces from there; fields missing on older
+                                        kernels are set to 0
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn parse_stat(content: &str) -> Option<sp::Stat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    if close < open {
+        return None;
+    }
+    let pid = content[..open].trim().parse().ok()?;
+    let comm = content[open + 1..close].to_string();
+    let rest: Vec<&str> = content[close + 1..].split_whitespace().collect();
+    let state = rest.first()?.chars().next()?;
+    // rest[0] is the state (field 3), so field N of proc(5) is rest[N - 3]
+    Some(sp::Stat {
+        pid,
+        comm,
+        state,
+        ppid: field(&rest, 1),
+        pgrp: field(&rest, 2),
+        session: field(&rest, 3),
+        tty_nr: field(&rest, 4),
+        tpgid: field(&rest, 5),
+        flags: field(&rest, 6),
+        minflt: field(&rest, 7),
+        cminflt: field(&rest, 8),
+        majflt: field(&rest, 9),
+        cmajflt: field(&rest, 10),
+        utime: field(&rest, 11),
+        stime: field(&rest, 12),
+        cutime: field(&rest, 13),
+        cstime: field(&rest, 14),
+        priority: field(&rest, 15),
+        nice: field(&rest, 16),
+        num_threads: field(&rest, 17),
+        itrealvalue: field(&rest, 18),
+        starttime: field(&rest, 19),
+        vsize: field(&rest, 20),
+        rss: field(&rest, 21),
+        rsslim: field(&rest, 22),
+        startcode: field(&rest, 23),
+        endcode: field(&rest, 24),
+        startstack: field(&rest, 25),
+        kstkesp: field(&rest, 26),
+        kstkeip: field(&rest, 27),
+        signal: field(&rest, 28),
+        blocked: field(&rest, 29),
+        sigignore: field(&rest, 30),
+        sigcatch: field(&rest, 31),
+        wchan: field(&rest, 32),
+        nswap: field(&rest, 33),
+        cnswap: field(&rest, 34),
+        exit_signal: field(&rest, 35),
+        processor: field(&rest, 36),
+        rt_priority: field(&rest, 37),
+        policy: field(&rest, 38),
+        delayacct_blkio_ticks: field(&rest, 39),
+        guest_time: field(&rest, 40),
+        cguest_time: field(&rest, 41),
+        start_data: field(&rest, 42),
+        end_data: field(&rest, 43),
+        start_brk: field(&rest, 44),
+        arg_start: field(&rest, 45),
+        arg_end: field(&rest, 46),
+        env_start: field(&rest, 47),
+        env_end: field(&rest, 48),
+        exit_code: field(&rest, 49),
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_stat: -input:         a process id
+                    -output:        the parsed Stat of this process; an Error if it does not exist or is malformed
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_stat(pid: usize) -> Result<sp::Stat, io::Error> {
+    let path = procfs::pid_path(pid, "stat");
+    let content = fs::read_to_string(&path)?;
+    parse_stat(&content).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("malformed {}", path.display())))
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function clock_ticks:   -input:         /
+                        -output:        the number of clock ticks per second (unit of the times in /proc/{PID}/stat)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn clock_ticks() -> f64 {
+    let tick = unsafe{sysconf(_SC_CLK_TCK)};
+    if tick > 0 { tick as f64 } else { 100.0 }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function lifetime_cpu_percent:  -input:         a Stat and the system uptime in seconds
+                                -output:        the CPU usage of the process averaged over its whole life (as ps does)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn lifetime_cpu_percent(stat: &sp::Stat, uptime: f64) -> f64 {
+    let tick = clock_ticks();
+    let elapsed = uptime - stat.starttime as f64 / tick;
+    if elapsed <= 0.0 {
+        return 0.0;
+    }
+    (stat.utime + stat.stime) as f64 / tick / elapsed * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAIL: &str = "S 1 1234 1234 0 -1 4194560 1500 0 3 0 25 10 0 0 20 0 1 0 5000 12345678 900 18446744073709551615 \
+        1 1 0 0 0 0 0 3670016 1266777851 0 0 0 17 2 0 0 7 0 0 0 0 0 0 0 0 0";
+
+    #[test]
+    fn comm_with_spaces() {
+        let stat = parse_stat(&format!("1234 (tmux: server) {}", TAIL)).unwrap();
+        assert_eq!(stat.pid, 1234);
+        assert_eq!(stat.comm, "tmux: server");
+        assert_eq!(stat.state, 'S');
+        assert_eq!(stat.ppid, 1);
+        assert_eq!(stat.utime, 25);
+        assert_eq!(stat.stime, 10);
+        assert_eq!(stat.starttime, 5000);
+        assert_eq!(stat.processor, 2);
+        assert_eq!(stat.delayacct_blkio_ticks, 7);
+    }
+
+    #[test]
+    fn comm_with_closing_paren() {
+        let stat = parse_stat(&format!("42 (a) b (c)) {}", TAIL)).unwrap();
+        assert_eq!(stat.pid, 42);
+        assert_eq!(stat.comm, "a) b (c)");
+        assert_eq!(stat.state, 'S');
+        assert_eq!(stat.num_threads, 1);
+    }
+
+    #[test]
+    fn truncated_line() {
+        assert!(parse_stat("1234 (bash").is_none());
+        assert!(parse_stat("1234 (bash) ").is_none());
+        assert!(parse_stat("1234 bash) S 1").is_none());
+        assert!(parse_stat("").is_none());
+    }
+
+    #[test]
+    fn older_kernel_fields() {
+        // 2.6 kernels stop after the scheduling policy (field 41)
+        let stat = parse_stat("7 (init) R 0 7 7 0 -1 0 10 0 0 0 3 4 0 0 20 0 1 0 100 2000 50 4294967295 \
+            1 1 0 0 0 0 0 0 0 0 0 0 17 0 0 1").unwrap();
+        assert_eq!(stat.state, 'R');
+        assert_eq!(stat.utime, 3);
+        assert_eq!(stat.policy, 1);
+        assert_eq!(stat.delayacct_blkio_ticks, 0);
+        assert_eq!(stat.exit_code, 0);
+    }
+}
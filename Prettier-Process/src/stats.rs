@@ -1,87 +1,95 @@
-use crate::struct_proc as sp;
-use crate::proc::{read_info};
-use std::fs;
+use crate::output::OutputOptions;
+use ppsx::mem::{read_meminfo, read_smaps_rollup};
+use ppsx::proc::get_all_info;
+use ppsx::struct_proc as sp;
+use ppsx::system::{read_activity, CPU_WINDOW};
 
 /*
 ------------------------------------------------------------------------------------------------------------------------
-Function statistics:    -input:         options as boleans
-                        -output:        Result type (did it succed or not)
-                        -description:   get all processes (ProcessInfo) public informations (with read_info) and
-                                        push them in a vector; then compute generals information and display the
-                                        result depending on the options
+Function format_uptime: -input:         a number of seconds
+                        -output:        a readable duration (ex: "3d 04h 12m 09s")
 ------------------------------------------------------------------------------------------------------------------------
 */
-pub fn statistics(json: bool, file: bool)-> Result<(), Box<dyn std::error::Error>>{
-    let mut pids: Vec<sp::ProcessInfo> = Vec::new();
-
-    let contents = fs::read_dir("/proc")?;
-
-    for instance in contents {
-        let instance = instance?;
-        let instance = instance.file_name().into_string().unwrap();
-
-        if let Ok(pid) = instance.parse::<usize>() {
-            if let Ok(info) = read_info(pid) {
-                pids.push(info);
-            }
-        }
-    }
-
-    let mut total = 0;
-    let mut run = 0;
-    let mut sleep = 0;
-    let mut disk_sleep = 0;
-    let mut zombie = 0;
-    let mut stop = 0;
-    let mut idle = 0;
-    let mut res_mem = 0;
-    let mut vr_mem = 0;
+fn format_uptime(seconds: f64)-> String{
+    let s = seconds as u64;
+    format!("{}d {:02}h {:02}m {:02}s", s / 86400, s % 86400 / 3600, s % 3600 / 60, s % 60)
+}
 
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function collect:   -input:         /
+                    -output:        the Statistics of the system; an Error if the processes or /proc/stat are not
+                                    readable
+                    -description:   get all processes (ProcessInfo) public informations (with get_all_info) in a
+                                    vector; then compute generals information and add the system memory and activity
+                                    (cpu usage, load, counters)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn collect()-> Result<sp::Statistics, Box<dyn std::error::Error>>{
+    let pids: Vec<sp::ProcessInfo> = get_all_info()?;
+    let mut stats = sp::Statistics{
+        total_processes: 0, running: 0, sleeping: 0, disk_sleeping: 0, zombie: 0, stopped: 0, idle: 0,
+        pss_memory_kb: 0, uss_memory_kb: 0, swap_pss_kb: 0, memory_accounted_processes: 0, rss_memory_kb: 0, virtual_memory_kb: 0,
+        meminfo: read_meminfo(),
+        system: read_activity()?,
+    };
     for p in pids{
-        total += 1;
+        stats.total_processes += 1;
         match p.state.as_str(){
-            "R" => run += 1,
-            "S" => sleep += 1,
-            "D" => disk_sleep += 1,
-            "Z" => zombie += 1,
-            "T" => stop += 1,
-            "I" => idle += 1,
+            "R" => stats.running += 1,
+            "S" => stats.sleeping += 1,
+            "D" => stats.disk_sleeping += 1,
+            "Z" => stats.zombie += 1,
+            "T" | "t" => stats.stopped += 1,
+            "I" => stats.idle += 1,
             _ => {}
         }
-        res_mem += p.vm_rss;
-        vr_mem += p.vm_size;
-    }
-
-    if json{
-        let stats = serde_json::json!({
-            "total_processes": total,
-            "running": run,
-            "sleeping": sleep,
-            "disk_sleeping": disk_sleep,
-            "zombie": zombie,
-            "stopped": stop,
-            "idle": idle,
-            "rss_memory_kb": res_mem,
-            "virtual_memory_kb": vr_mem
-        });
-        let output = serde_json::to_string_pretty(&stats)?;
-        if !file {
-            println!("{}", output);
-        } else {
-            println!("===== Creating the file =====");
-            fs::write("./statistics.json", output)?;
-            println!("===== Creation completed =====");
+        stats.rss_memory_kb += p.vm_rss;
+        stats.virtual_memory_kb += p.vm_size;
+        if let Some(smaps) = read_smaps_rollup(p.pid){
+            stats.pss_memory_kb += smaps.pss;
+            stats.uss_memory_kb += smaps.uss;
+            stats.swap_pss_kb += smaps.swap_pss;
+            stats.memory_accounted_processes += 1;
         }
     }
-    else{
-        let output = format!("===== Statistics =====\n--- Processes Status ---\nTotal processes: {total}\nRunning: {run}\nSleeping: {sleep}\nDisk Sleep: {disk_sleep}\nZombie: {zombie}\nStopped: {stop}\nIdle: {idle}\n--- Memory Usage ---\nTotal RSS Memory: {res_mem} kB ({} MB)\nTotal Virtual Memory: {vr_mem} kB ({} MB)", res_mem / 1024, vr_mem / 1024);
-        if !file {
-            println!("{}", output);
-        } else {
-            println!("===== Creating the file =====");
-            fs::write("./statistics.txt", output)?;
-            println!("===== Creation completed =====");
+    Ok(stats)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function statistics:    -input:         the output options
+                        -output:        Result type (did it succed or not)
+                        -description:   collect the statistics of the system and display them in the chosen format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn statistics(out: &OutputOptions)-> Result<(), Box<dyn std::error::Error>>{
+    let stats = collect()?;
+    let (meminfo, activity) = (&stats.meminfo, &stats.system);
+    out.emit(&stats, || {
+        let mut output = format!("===== Statistics =====\n--- Processes Status ---\nTotal processes: {}\nRunning: {}\nSleeping: {}\nDisk Sleep: {}\nZombie: {}\nStopped: {}\nIdle: {}\n", stats.total_processes, stats.running, stats.sleeping, stats.disk_sleeping, stats.zombie, stats.stopped, stats.idle);
+        output.push_str(&format!("--- Memory Usage ---\nTotal PSS Memory: {} kB ({} MB) over {} processes\nTotal USS Memory: {} kB ({} MB)\nTotal Swap (PSS): {} kB ({} MB)\nSum of RSS: {} kB ({} MB) (counts shared pages once per process)\nTotal Virtual Memory: {} kB ({} MB)",
+            stats.pss_memory_kb, stats.pss_memory_kb / 1024, stats.memory_accounted_processes, stats.uss_memory_kb, stats.uss_memory_kb / 1024, stats.swap_pss_kb, stats.swap_pss_kb / 1024,
+            stats.rss_memory_kb, stats.rss_memory_kb / 1024, stats.virtual_memory_kb, stats.virtual_memory_kb / 1024));
+        output.push_str("\n--- System Memory ---\n");
+        output.push_str(&format!("MemTotal: {} kB ({} MB)\n", meminfo.mem_total, meminfo.mem_total / 1024));
+        output.push_str(&format!("MemFree: {} kB ({} MB)\n", meminfo.mem_free, meminfo.mem_free / 1024));
+        output.push_str(&format!("MemAvailable: {} kB ({} MB)\n", meminfo.mem_available, meminfo.mem_available / 1024));
+        output.push_str(&format!("Buffers: {} kB | Cached: {} kB | Shmem: {} kB\n", meminfo.buffers, meminfo.cached, meminfo.shmem));
+        output.push_str(&format!("Swap: {} kB used of {} kB (cached: {} kB)\n", meminfo.swap_total.saturating_sub(meminfo.swap_free), meminfo.swap_total, meminfo.swap_cached));
+        output.push_str(&format!("HugePages: {} free of {} ({} kB each) | AnonHugePages: {} kB", meminfo.hugepages_free, meminfo.hugepages_total, meminfo.hugepage_size, meminfo.anon_hugepages));
+        output.push_str("\n--- System Activity ---\n");
+        output.push_str(&format!("Uptime: {}\n", format_uptime(activity.uptime_seconds)));
+        output.push_str(&format!("Load average: {:.2} {:.2} {:.2}\n", activity.load_average[0], activity.load_average[1], activity.load_average[2]));
+        output.push_str(&format!("Context switches: {}\n", activity.context_switches));
+        output.push_str(&format!("Forks since boot: {}\n", activity.forks));
+        output.push_str(&format!("Runnable: {} | Blocked: {}\n", activity.procs_running, activity.procs_blocked));
+        output.push_str(&format!("--- CPU Usage (over {} ms) ---\n", CPU_WINDOW.as_millis()));
+        output.push_str(&format!("{:<6} {:>7} {:>7} {:>7} {:>7} {:>7}", "CPU", "USER%", "SYS%", "IOWAIT%", "STEAL%", "IDLE%"));
+        for cpu in std::iter::once(&activity.cpu_total).chain(activity.cpu_cores.iter()){
+            output.push_str(&format!("\n{:<6} {:>7.2} {:>7.2} {:>7.2} {:>7.2} {:>7.2}", if cpu.cpu == "cpu" { "all" } else { cpu.cpu.as_str() },
+                cpu.user, cpu.system, cpu.iowait, cpu.steal, cpu.idle));
         }
-    }
-    Ok(())
+        output
+    })
 }
\ No newline at end of file
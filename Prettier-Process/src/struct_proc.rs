@@ -5,95 +5,911 @@ depending on the command and its option(s).
 ----------------------------------------------------------------------------------------
 */
 
-use serde::Serialize;
+//! Structures returned by the readers, serialized as is by the `--format` outputs of `ppsx`.
+
+use serde::{Deserialize, Serialize};
 
 macro_rules! pub_struct {
-    ($name:ident { $($field:ident: $t:ty,)* }) => {
-        #[derive(Serialize)]
+    ($(#[$meta:meta])* $name:ident { $($(#[$field_meta:meta])* $field:ident: $t:ty,)* }) => {
+        $(#[$meta])*
+        #[derive(Serialize, Deserialize, Clone, Debug)]
         pub struct $name {
-            $(pub $field: $t,)*
+            $($(#[$field_meta])* pub $field: $t,)*
         }
     }
 }
 
-pub_struct!(Process{
-    pid: usize,
-    name: String,
-}); // Used for list
-
-pub_struct!(ProcessInfo{
+pub_struct!(
+    /// Main information of a process, from `/proc/PID/status`, `comm` and `cmdline`.
+    ProcessInfo{
+    /// Process id.
     pid: usize,
+    /// Name of the executable (`comm`, at most 15 characters).
     name: String,
+    /// State letter (R, S, D, Z, T, t, X, I).
     state: String,
+    /// Parent process id.
     ppid: usize,
+    /// Real user id.
     uid: u32,
+    /// Real group id.
     gid: u32,
+    /// Effective user id, used for the permission checks (0 in snapshots taken before it was recorded).
+    #[serde(default)]
+    euid: u32,
+    /// Effective group id (0 in snapshots taken before it was recorded).
+    #[serde(default)]
+    egid: u32,
+    /// Number of threads.
     threads: usize,
+    /// Virtual memory size, in kB.
     vm_size: usize,
+    /// Resident set size, in kB.
     vm_rss: usize,
+    /// Command line, arguments separated by spaces (empty for kernel threads).
     cmdline: String,
-}); // Used for the other commands
+});
+
+pub_struct!(
+    /// Memory accounting of a process from `/proc/PID/smaps_rollup`, all in kB.
+    SmapsRollup{
+    /// Resident set size.
+    rss: u64,
+    /// Proportional set size: every shared page divided between the processes mapping it.
+    pss: u64,
+    /// Unique set size: the private pages, freed if the process exited.
+    uss: u64,
+    /// Shared pages not modified.
+    shared_clean: u64,
+    /// Shared pages modified.
+    shared_dirty: u64,
+    /// Private pages not modified.
+    private_clean: u64,
+    /// Private pages modified.
+    private_dirty: u64,
+    /// Memory swapped out.
+    swap: u64,
+    /// Proportional share of the swapped out memory.
+    swap_pss: u64,
+});
+
+pub_struct!(
+    /// One memory mapping of a process from `/proc/PID/smaps` (sizes in kB).
+    MapEntry{
+    /// Start address, in hexadecimal.
+    start: String,
+    /// End address, in hexadecimal.
+    end: String,
+    /// Size of the mapping.
+    size_kb: u64,
+    /// Permissions (ex: `r-xp`).
+    perms: String,
+    /// Offset in the backing file, in hexadecimal.
+    offset: String,
+    /// Backing file or pseudo path (`[heap]`, `[stack]`...), `[anon]` for anonymous mappings.
+    path: String,
+    /// Resident part.
+    rss: u64,
+    /// Proportional set size.
+    pss: u64,
+    /// Swapped out part.
+    swap: u64,
+    /// Anonymous pages.
+    anonymous: u64,
+    /// Anonymous pages backed by transparent huge pages.
+    thp: u64,
+});
+
+pub_struct!(
+    /// Mappings of a process grouped by backing file (sizes in kB).
+    MapGroup{
+    /// Backing file or pseudo path shared by the mappings.
+    path: String,
+    /// Number of mappings.
+    mappings: usize,
+    /// Total size.
+    size_kb: u64,
+    /// Resident part.
+    rss: u64,
+    /// Proportional set size.
+    pss: u64,
+    /// Swapped out part.
+    swap: u64,
+    /// Anonymous pages.
+    anonymous: u64,
+    /// Anonymous pages backed by transparent huge pages.
+    thp: u64,
+});
+
+pub_struct!(
+    /// System memory from `/proc/meminfo`, in kB except the hugepages counts.
+    MemInfo{
+    /// Usable RAM.
+    mem_total: u64,
+    /// RAM left unused.
+    mem_free: u64,
+    /// Estimate of the RAM available to new programs without swapping.
+    mem_available: u64,
+    /// Block device buffers.
+    buffers: u64,
+    /// Page cache.
+    cached: u64,
+    /// Shared memory and tmpfs.
+    shmem: u64,
+    /// Swap space.
+    swap_total: u64,
+    /// Swap space left unused.
+    swap_free: u64,
+    /// Swapped out memory also still in RAM.
+    swap_cached: u64,
+    /// Anonymous memory backed by transparent huge pages.
+    anon_hugepages: u64,
+    /// Number of preallocated huge pages.
+    hugepages_total: u64,
+    /// Number of preallocated huge pages not in use.
+    hugepages_free: u64,
+    /// Size of a huge page.
+    hugepage_size: u64,
+});
+
+pub_struct!(
+    /// One thread of a process from `/proc/PID/task/TID`.
+    ThreadInfo{
+    /// Thread id.
+    tid: usize,
+    /// Name of the thread.
+    name: String,
+    /// State letter.
+    state: String,
+    /// User + system time, in clock ticks.
+    cpu_ticks: u64,
+    /// Cpu the thread last ran on.
+    last_cpu: i32,
+    /// Context switches because the thread waited.
+    voluntary_switches: u64,
+    /// Context switches because the thread was preempted.
+    nonvoluntary_switches: u64,
+});
+
+pub_struct!(
+    /// One thread at one reading, with its cpu usage since the previous one.
+    ThreadSample{
+    /// Process id.
+    pid: usize,
+    /// Thread id.
+    tid: usize,
+    /// Name of the thread.
+    name: String,
+    /// State letter.
+    state: String,
+    /// Cpu usage in %, `None` on the first reading of the thread.
+    cpu_percent: Option<f64>,
+    /// Cpu the thread last ran on.
+    last_cpu: i32,
+    /// Context switches because the thread waited.
+    voluntary_switches: u64,
+    /// Context switches because the thread was preempted.
+    nonvoluntary_switches: u64,
+    /// Local time of the reading.
+    timestamp: String,
+});
+
+pub_struct!(
+    /// Storage I/O of a process from `/proc/PID/io`.
+    IoCounters{
+    /// Bytes read from storage.
+    read_bytes: u64,
+    /// Bytes written to storage.
+    write_bytes: u64,
+    /// Read syscalls.
+    read_count: u64,
+    /// Write syscalls.
+    write_count: u64,
+    /// Bytes written then truncated before reaching storage.
+    cancelled_write_bytes: u64,
+});
+
+pub_struct!(
+    /// Share of time (in %) spent by a cpu, or all of them, in each state.
+    CpuUsage{
+    /// `cpu` for all of them, `cpuN` for one core.
+    cpu: String,
+    /// User and nice time.
+    user: f64,
+    /// System, irq and softirq time.
+    system: f64,
+    /// Idle time waiting for I/O.
+    iowait: f64,
+    /// Time taken by the hypervisor for other guests.
+    steal: f64,
+    /// Idle time.
+    idle: f64,
+});
 
-pub_struct!(FullProcessInfo{
+pub_struct!(
+    /// System-wide figures from `/proc/stat`, `/proc/loadavg` and `/proc/uptime`.
+    SystemActivity{
+    /// Time since boot.
+    uptime_seconds: f64,
+    /// Load average over 1, 5 and 15 minutes.
+    load_average: [f64; 3],
+    /// Context switches since boot.
+    context_switches: u64,
+    /// Processes and threads created since boot.
+    forks: u64,
+    /// Runnable threads.
+    procs_running: u64,
+    /// Threads blocked on I/O.
+    procs_blocked: u64,
+    /// Usage of all the cpus.
+    cpu_total: CpuUsage,
+    /// Usage of every core.
+    cpu_cores: Vec<CpuUsage>,
+});
+
+pub_struct!(
+    /// Overview of the processes and of the system, shown by `stats` and `serve`.
+    Statistics{
+    /// Number of processes.
+    total_processes: usize,
+    /// Processes in state R.
+    running: usize,
+    /// Processes in state S.
+    sleeping: usize,
+    /// Processes in state D.
+    disk_sleeping: usize,
+    /// Processes in state Z.
+    zombie: usize,
+    /// Processes in state T or t.
+    stopped: usize,
+    /// Kernel threads in state I.
+    idle: usize,
+    /// Sum of the PSS of the processes, in kB.
+    pss_memory_kb: u64,
+    /// Sum of the USS of the processes, in kB.
+    uss_memory_kb: u64,
+    /// Sum of the swap PSS of the processes, in kB.
+    swap_pss_kb: u64,
+    /// Processes whose `smaps_rollup` was readable, counted in the PSS/USS sums.
+    memory_accounted_processes: usize,
+    /// Sum of the RSS of the processes (shared pages counted several times), in kB.
+    rss_memory_kb: usize,
+    /// Sum of the virtual memory of the processes, in kB.
+    virtual_memory_kb: usize,
+    /// System memory.
+    meminfo: MemInfo,
+    /// System-wide activity.
+    system: SystemActivity,
+});
+
+pub_struct!(
+    /// One process at one tick of `live`.
+    LiveSample{
+    /// Process id.
+    pid: usize,
+    /// Name of the process.
+    name: String,
+    /// State letter.
+    state: String,
+    /// Cpu usage in % since the previous tick, `None` on its first tick.
+    cpu_percent: Option<f64>,
+    /// Resident set size, in kB.
+    memory_rss_kb: usize,
+    /// Virtual memory size, in kB.
+    memory_virtual_kb: usize,
+    /// Number of threads.
+    threads: usize,
+    /// Local time of the tick.
+    timestamp: String,
+});
+
+pub_struct!(
+    /// Summary of one process at the end of `live`.
+    LiveSummary{
+    /// Process id.
+    pid: usize,
+    /// Name of the process.
+    name: String,
+    /// Number of ticks the process was seen.
+    samples: usize,
+    /// Number of ticks with a cpu usage.
+    cpu_samples: usize,
+    /// Lowest cpu usage, in %.
+    cpu_min: Option<f64>,
+    /// Highest cpu usage, in %.
+    cpu_max: Option<f64>,
+    /// Average cpu usage, in %.
+    cpu_avg: Option<f64>,
+    /// Highest resident set size, in kB.
+    peak_rss_kb: usize,
+});
+
+pub_struct!(
+    /// Alert rule of `live` that fired for a process.
+    AlertEvent{
+    /// Local time of the tick.
+    timestamp: String,
+    /// Process id.
+    pid: usize,
+    /// Name of the process.
+    name: String,
+    /// The `--alert` rule as given.
+    rule: String,
+    /// Value that fired the rule (ex: `93.50%`, `2150400 kB`, `D`).
+    value: String,
+});
+
+pub_struct!(
+    /// Process start, exec or exit seen by `events`.
+    ProcessEvent{
+    /// Local time of the event.
+    timestamp: String,
+    /// `fork`, `exec` or `exit`.
+    event: String,
+    /// Process id.
+    pid: usize,
+    /// Parent process id.
+    ppid: usize,
+    /// Name of the process.
+    name: String,
+    /// Command line of the process.
+    cmdline: String,
+    /// Exit code, on exit only and when known.
+    exit_code: Option<i32>,
+    /// Signal that killed the process, on exit only.
+    signal: Option<String>,
+});
+
+pub_struct!(
+    /// One process of the `tree`, with its children. The optional fields are only filled when
+    /// the matching column is asked.
+    TreeNode{
+    /// Process id.
+    pid: usize,
+    /// Name of the process.
+    name: String,
+    /// State letter.
+    state: String,
+    /// Number of threads.
+    threads: Option<usize>,
+    /// Resident set size, in kB.
+    vm_rss: Option<usize>,
+    /// Cpu usage, in %.
+    cpu_percent: Option<f64>,
+    /// Child processes.
+    children: Vec<TreeNode>,
+});
+
+pub_struct!(
+    /// Every field of `/proc/PID/stat`, named as in proc(5). Fields missing on older kernels are 0.
+    Stat{
+    /// Process id.
+    pid: usize,
+    /// Name of the executable, may contain spaces and parenthesis.
+    comm: String,
+    /// State letter.
+    state: char,
+    /// Parent process id.
+    ppid: usize,
+    /// Process group id.
+    pgrp: i32,
+    /// Session id.
+    session: i32,
+    /// Controlling terminal (device number), 0 without.
+    tty_nr: i32,
+    /// Foreground process group of the controlling terminal.
+    tpgid: i32,
+    /// Kernel flags (PF_*).
+    flags: u32,
+    /// Minor page faults.
+    minflt: u64,
+    /// Minor page faults of the waited-for children.
+    cminflt: u64,
+    /// Major page faults.
+    majflt: u64,
+    /// Major page faults of the waited-for children.
+    cmajflt: u64,
+    /// User time, in clock ticks.
+    utime: u64,
+    /// System time, in clock ticks.
+    stime: u64,
+    /// User time of the waited-for children, in clock ticks.
+    cutime: i64,
+    /// System time of the waited-for children, in clock ticks.
+    cstime: i64,
+    /// Scheduling priority as seen by the kernel.
+    priority: i64,
+    /// Nice value (-20 to 19).
+    nice: i64,
+    /// Number of threads.
+    num_threads: i64,
+    /// Obsolete, always 0.
+    itrealvalue: i64,
+    /// Start time after boot, in clock ticks.
+    starttime: u64,
+    /// Virtual memory size, in bytes.
+    vsize: u64,
+    /// Resident set size, in pages.
+    rss: i64,
+    /// Soft limit of the rss, in bytes.
+    rsslim: u64,
+    /// Start address of the program text.
+    startcode: u64,
+    /// End address of the program text.
+    endcode: u64,
+    /// Start address of the stack.
+    startstack: u64,
+    /// Current stack pointer (0 unless traced).
+    kstkesp: u64,
+    /// Current instruction pointer (0 unless traced).
+    kstkeip: u64,
+    /// Pending signals (obsolete bitmap).
+    signal: u64,
+    /// Blocked signals (obsolete bitmap).
+    blocked: u64,
+    /// Ignored signals (obsolete bitmap).
+    sigignore: u64,
+    /// Caught signals (obsolete bitmap).
+    sigcatch: u64,
+    /// Wait channel placeholder (0 or 1 on recent kernels).
+    wchan: u64,
+    /// Not maintained, always 0.
+    nswap: u64,
+    /// Not maintained, always 0.
+    cnswap: u64,
+    /// Signal sent to the parent when the process dies.
+    exit_signal: i32,
+    /// Cpu the process last ran on.
+    processor: i32,
+    /// Real-time priority (1 to 99), 0 for normal policies.
+    rt_priority: u32,
+    /// Scheduling policy (SCHED_* constant).
+    policy: u32,
+    /// Time spent waiting for block I/O, in clock ticks.
+    delayacct_blkio_ticks: u64,
+    /// Time spent running a virtual cpu, in clock ticks.
+    guest_time: u64,
+    /// Guest time of the waited-for children, in clock ticks.
+    cguest_time: i64,
+    /// Start address of the initialized and uninitialized data.
+    start_data: u64,
+    /// End address of the initialized and uninitialized data.
+    end_data: u64,
+    /// Start address of the heap.
+    start_brk: u64,
+    /// Start address of the command line arguments.
+    arg_start: u64,
+    /// End address of the command line arguments.
+    arg_end: u64,
+    /// Start address of the environment.
+    env_start: u64,
+    /// End address of the environment.
+    env_end: u64,
+    /// Exit status, as reported by waitpid(2).
+    exit_code: i32,
+});
+
+pub_struct!(
+    /// One TCP or UDP socket of a process.
+    SocketEntry{
+    /// `tcp`, `tcp6`, `udp` or `udp6`.
+    protocol: String,
+    /// Inode of the socket.
+    inode: u64,
+    /// Local address.
+    local_address: String,
+    /// Local port.
+    local_port: u16,
+    /// Remote address.
+    remote_address: String,
+    /// Remote port.
+    remote_port: u16,
+    /// TCP state (ex: `LISTEN`, `ESTABLISHED`), `CLOSE` or `ESTABLISHED` for UDP.
+    state: String,
+    /// Bytes waiting to be sent.
+    tx_queue: u64,
+    /// Bytes waiting to be read.
+    rx_queue: u64,
+});
+
+pub_struct!(
+    /// One Unix socket of a process.
+    UnixSocketEntry{
+    /// Inode of the socket.
+    inode: u64,
+    /// `STREAM`, `DGRAM` or `SEQPACKET`.
+    socket_type: String,
+    /// `UNCONNECTED`, `CONNECTING`, `CONNECTED` or `DISCONNECTING`.
+    state: String,
+    /// Whether the socket accepts connections.
+    listening: bool,
+    /// Bound path (starting with `@` for the abstract namespace), `None` if unbound.
+    path: Option<String>,
+});
+
+pub_struct!(
+    /// Scheduler details from `/proc/PID/sched`, `None` when the kernel does not expose them.
+    SchedInfo{
+    /// Context switches.
+    nr_switches: Option<u64>,
+    /// Context switches because the process waited.
+    nr_voluntary_switches: Option<u64>,
+    /// Context switches because the process was preempted.
+    nr_involuntary_switches: Option<u64>,
+    /// Moves to another cpu.
+    nr_migrations: Option<u64>,
+    /// Time spent running.
+    sum_exec_runtime_ms: Option<f64>,
+    /// Time spent waiting for a cpu.
+    wait_sum_ms: Option<f64>,
+    /// Virtual runtime used by the fair scheduler.
+    vruntime_ms: Option<f64>,
+});
+
+pub_struct!(
+    /// Everything known about a process, shown by `pinfo --all`. Fields that need more
+    /// privileges than the caller has are empty, `None` or `N/A`.
+    FullProcessInfo{
+        /// Process id.
         pid: usize,
+        /// Name of the executable.
         name: String,
+        /// Command line, arguments separated by spaces.
         cmdline: String,
+        /// State letter.
         state: String,
+        /// Parent process id.
         ppid: usize,
+        /// Number of threads.
         threads: usize,
+        /// Real user id.
         uid: u32,
+        /// Real group id.
         gid: u32,
+        /// User time, in clock ticks.
         utime: u64,
+        /// System time, in clock ticks.
         stime: u64,
+        /// User time of the waited-for children, in clock ticks.
+        cutime: i64,
+        /// System time of the waited-for children, in clock ticks.
+        cstime: i64,
+        /// Scheduling priority as seen by the kernel.
         prio: i32,
+        /// Nice value.
         nice: i32,
+        /// Cpu the process last ran on.
+        processor: i32,
+        /// Minor page faults.
+        minflt: u64,
+        /// Major page faults.
+        majflt: u64,
+        /// Time spent waiting for block I/O, in clock ticks.
+        blkio_ticks: u64,
+        /// Virtual memory size, in kB.
         vm_size: usize,
+        /// Resident set size, in kB.
         vm_rss: usize,
+        /// Data segment and heap, in kB.
         vm_data: usize,
+        /// Stack of the main thread, in kB.
         vm_stack: usize,
+        /// Program text, in kB.
         vm_exe: usize,
+        /// Shared libraries, in kB.
         vm_lib: usize,
+        /// Swapped out memory, in kB.
         vm_swap: usize,
+        /// Locked memory, in kB.
         vm_locked: usize,
+        /// Highest resident set size, in kB.
         vm_hwm: usize,
+        /// Highest virtual memory size, in kB.
         vm_peak: usize,
+        /// PSS/USS accounting, `None` if `smaps_rollup` is not readable.
+        smaps: Option<SmapsRollup>,
+        /// Bytes read from storage.
         read_bytes: Option<u64>,
+        /// Bytes written to storage.
         write_bytes: Option<u64>,
+        /// Read syscalls.
         read_count: Option<u64>,
+        /// Write syscalls.
         write_count: Option<u64>,
+        /// Bytes written then truncated before reaching storage.
         cancelled_write_bytes: Option<u64>,
+        /// Number of open file descriptors.
         fd_count: usize,
+        /// Targets of the open file descriptors.
         open_files: Vec<String>,
+        /// Working directory.
         cwd: String,
+        /// Path of the executable.
         exe: String,
+        /// Root directory.
         root: String,
+        /// Soft limit of the cpu time (`/proc/PID/limits`), like the following `mx` fields.
         mxcpu_time: String,
+        /// Soft limit of the file size.
         mxfile_size: String,
+        /// Soft limit of the data segment size.
         mxdata_size: String,
+        /// Soft limit of the stack size.
         mxstack_size: String,
+        /// Soft limit of the core file size.
         mxcore_file_size: String,
+        /// Soft limit of the resident set.
         mxresident_set: String,
+        /// Soft limit of the number of processes of the user.
         mxprocesses: String,
+        /// Soft limit of the open files.
         mxopen_files: String,
+        /// Soft limit of the locked memory.
         mxlocked_memory: String,
+        /// Soft limit of the address space.
         mxaddress_space: String,
+        /// Soft limit of the file locks.
         mxfile_locks: String,
+        /// Soft limit of the pending signals.
         mxpending_signals: String,
+        /// Soft limit of the POSIX message queues size.
         mxmsgqueue_size: String,
+        /// Soft limit of the nice priority.
         mxnice_prio: String,
+        /// Soft limit of the real-time priority.
         mxrealtime_prio: String,
+        /// Soft limit of the real-time cpu time without blocking.
         mxrealtime_timeout: String,
-        tcp_connections: Vec<String>,
-        udp_connections: Vec<String>,
-        unix_sockets: Vec<String>,
+        /// TCP sockets (IPv4 and IPv6).
+        tcp_connections: Vec<SocketEntry>,
+        /// UDP sockets (IPv4 and IPv6).
+        udp_connections: Vec<SocketEntry>,
+        /// Unix sockets.
+        unix_sockets: Vec<UnixSocketEntry>,
+        /// Scheduling policy (ex: `SCHED_OTHER`).
         policy: String,
+        /// Real-time priority.
         rt_prio: u32,
+        /// Scheduler details.
+        sched: SchedInfo,
+        /// Cpus the process may run on (ex: `0-3,6`).
+        cpu_affinity: String,
+        /// I/O priority (ex: `best-effort/4`), `N/A` if unknown.
+        io_priority: String,
+        /// Environment variables as `KEY=VALUE`.
         environment: Vec<String>,
+        /// Lines of `/proc/PID/numa_maps`.
         numa_maps: Vec<String>,
+        /// Lines of `/proc/PID/cgroup`.
         cgroups: Vec<String>,
+        /// Cgroup v2 limits and usage, `None` without cgroup v2.
+        cgroup: Option<CgroupInfo>,
+        /// Container the process runs in.
+        container: ContainerInfo,
+        /// Uids, gids and groups.
+        credentials: Credentials,
+        /// Current syscall (`/proc/PID/syscall`).
         syscall: Option<String>,
+        /// Kernel function the process sleeps in.
         wchan: Option<String>,
+        /// Start time after boot, in clock ticks.
         sttime: u64,
+        /// System uptime, in seconds.
         uptime: u64,
     }
-); // Used for --all option
+);
+
+pub_struct!(
+    /// I/O of a cgroup on one block device, from `io.stat`.
+    CgroupIo{
+    /// Kernel name of the device (ex: `sda`), `MAJOR:MINOR` if unknown.
+    device: String,
+    /// Bytes read.
+    read_bytes: u64,
+    /// Bytes written.
+    write_bytes: u64,
+    /// Read operations.
+    read_ios: u64,
+    /// Write operations.
+    write_ios: u64,
+});
+
+pub_struct!(
+    /// Cgroup v2 of a process. A `None` field means that its file is missing (controller not
+    /// enabled for the group).
+    CgroupInfo{
+    /// Path as in `/proc/PID/cgroup`.
+    path: String,
+    /// Directory of the group under the cgroup2 mount.
+    directory: String,
+    /// Memory used, in bytes.
+    memory_current: Option<u64>,
+    /// Memory limit in bytes, or `max`.
+    memory_max: Option<String>,
+    /// `QUOTA PERIOD` in microseconds, QUOTA being `max` without limit.
+    cpu_max: Option<String>,
+    /// Cpus allowed by `cpu.max`, `None` without limit.
+    cpu_limit: Option<f64>,
+    /// Cpu time used, in microseconds.
+    cpu_usage_usec: Option<u64>,
+    /// Enforcement periods elapsed.
+    nr_periods: Option<u64>,
+    /// Periods in which the group was throttled.
+    nr_throttled: Option<u64>,
+    /// Time the group was throttled, in microseconds.
+    throttled_usec: Option<u64>,
+    /// Number of processes and threads.
+    pids_current: Option<u64>,
+    /// Limit of processes and threads, or `max`.
+    pids_max: Option<String>,
+    /// I/O per block device.
+    io: Vec<CgroupIo>,
+});
+
+pub_struct!(
+    /// Processes of a cgroup summed up, next to the limits of the group, shown by `cgroups`.
+    CgroupUsage{
+    /// Path of the cgroup.
+    path: String,
+    /// Number of processes.
+    processes: usize,
+    /// Number of threads.
+    threads: usize,
+    /// Sum of the resident set sizes, in kB.
+    rss_kb: usize,
+    /// Sum of the cpu usage of its processes, in %.
+    cpu_percent: f64,
+    /// Memory used by the group, in bytes.
+    memory_current: Option<u64>,
+    /// Memory limit in bytes, or `max`.
+    memory_max: Option<String>,
+    /// Cpus allowed, `None` without limit.
+    cpu_limit: Option<f64>,
+    /// Periods in which the group was throttled.
+    nr_throttled: Option<u64>,
+    /// Time the group was throttled, in microseconds.
+    throttled_usec: Option<u64>,
+});
+
+pub_struct!(
+    /// One namespace of a process.
+    Namespace{
+    /// `pid`, `net`, `mnt`, `user`, `uts`, `ipc` or `cgroup`.
+    name: String,
+    /// Inode of the namespace, `None` if `/proc/PID/ns` is not readable.
+    inode: Option<u64>,
+    /// Whether it is not the namespace of PID 1, `None` if one of them is not readable.
+    differs: Option<bool>,
+});
+
+pub_struct!(
+    /// Container a process runs in, recognized from its cgroup paths and namespaces.
+    ContainerInfo{
+    /// `docker`, `containerd`, `podman`, `cri-o`, `systemd-nspawn`, `lxc` or `kubernetes`.
+    runtime: Option<String>,
+    /// Container ID, or machine name for systemd-nspawn.
+    id: Option<String>,
+    /// Namespaces of the process.
+    namespaces: Vec<Namespace>,
+    /// Names of the namespaces not shared with PID 1.
+    differing: Vec<String>,
+});
+
+pub_struct!(
+    /// A uid or a gid with its name.
+    NamedId{
+    /// Numerical id.
+    id: u32,
+    /// Name from `/etc/passwd` or `/etc/group`, `None` if not listed.
+    name: Option<String>,
+});
+
+pub_struct!(
+    /// Identity of a process, from `/proc/PID/status`.
+    Credentials{
+    /// Real uid: the user who started the process.
+    real_uid: NamedId,
+    /// Effective uid, used for the permission checks.
+    effective_uid: NamedId,
+    /// Saved uid, that the process may switch back to.
+    saved_uid: NamedId,
+    /// Filesystem uid, used for the file accesses.
+    fs_uid: NamedId,
+    /// Real gid.
+    real_gid: NamedId,
+    /// Effective gid.
+    effective_gid: NamedId,
+    /// Saved gid.
+    saved_gid: NamedId,
+    /// Filesystem gid.
+    fs_gid: NamedId,
+    /// Supplementary groups.
+    groups: Vec<NamedId>,
+    /// Whether the effective uid or gid differs from the real one (setuid/setgid binary...).
+    setid: bool,
+});
+
+pub_struct!(
+    /// One process of a snapshot.
+    SnapshotEntry{
+    /// Main information of the process.
+    info: ProcessInfo,
+    /// Start time after boot in clock ticks, identifies the process together with its pid.
+    start_time: u64,
+    /// Number of open file descriptors, `None` if `/proc/PID/fd` is not readable.
+    fd_count: Option<usize>,
+});
+
+pub_struct!(
+    /// Processes captured by `snapshot`.
+    Snapshot{
+    /// Local time of the capture (RFC 3339).
+    timestamp: String,
+    /// Boot id of the system.
+    boot_id: String,
+    /// Boot time, in seconds since the epoch.
+    boot_time: u64,
+    /// Clock ticks per second, unit of the start times.
+    clock_ticks: u64,
+    /// Captured processes.
+    processes: Vec<SnapshotEntry>,
+});
+
+pub_struct!(
+    /// Process that exec'd another program between two snapshots.
+    CmdlineChange{
+    /// Process id.
+    pid: usize,
+    /// Name of the process in the later snapshot.
+    name: String,
+    /// Command line in the earlier snapshot.
+    before: String,
+    /// Command line in the later snapshot.
+    after: String,
+});
+
+pub_struct!(
+    /// Resources of a process present in both snapshots.
+    ProcessGrowth{
+    /// Process id.
+    pid: usize,
+    /// Name of the process.
+    name: String,
+    /// Resident set size in the earlier snapshot, in kB.
+    rss_before: usize,
+    /// Resident set size in the later snapshot, in kB.
+    rss_after: usize,
+    /// Change of the resident set size, in kB.
+    rss_delta: i64,
+    /// Threads in the earlier snapshot.
+    threads_before: usize,
+    /// Threads in the later snapshot.
+    threads_after: usize,
+    /// Change of the number of threads.
+    threads_delta: i64,
+    /// Open file descriptors in the earlier snapshot.
+    fd_before: Option<usize>,
+    /// Open file descriptors in the later snapshot.
+    fd_after: Option<usize>,
+    /// Change of the number of open file descriptors.
+    fd_delta: Option<i64>,
+});
+
+pub_struct!(
+    /// Differences between two snapshots, shown by `diff`.
+    SnapshotDiff{
+    /// Timestamp of the earlier snapshot.
+    before: String,
+    /// Timestamp of the later snapshot.
+    after: String,
+    /// False if the system rebooted in between: every process is then new.
+    same_boot: bool,
+    /// Processes only in the later snapshot.
+    new_processes: Vec<SnapshotEntry>,
+    /// Processes only in the earlier snapshot.
+    exited_processes: Vec<SnapshotEntry>,
+    /// Processes whose command line changed.
+    cmdline_changes: Vec<CmdlineChange>,
+    /// Processes whose rss, threads or fds changed, biggest rss growth first.
+    growth: Vec<ProcessGrowth>,
+});
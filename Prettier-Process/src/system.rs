@@ -0,0 +1,129 @@
+/*
+----------------------------------------------------------------------------------------
+File used to read the system-wide activity: cpu usage of the whole system and of each
+core, load average, uptime and the scheduler counters of /proc/stat.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::proc::read_uptime;
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+pub const CPU_WINDOW: Duration = Duration::from_millis(250); // time between the two readings of /proc/stat
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_cpu_times: -input:        the content of /proc/stat
+                         -output:       for every "cpu" line (the total first, then each core), its name and its
+                                        user, nice, system, idle, iowait, irq, softirq and steal ticks
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_cpu_times(content: &str)-> Vec<(String, [u64; 8])>{
+    content.lines()
+        .filter(|line| line.starts_with("cpu"))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next().unwrap_or("cpu").to_string();
+            let mut ticks = [0u64; 8];
+            for (slot, value) in ticks.iter_mut().zip(parts) {
+                *slot = value.parse().unwrap_or(0);
+            }
+            (name, ticks)
+        })
+        .collect()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function cpu_usage: -input:         the name of a cpu and two readings of its ticks (read_cpu_times)
+                    -output:        the share of time spent in each state between both readings; nice is counted as
+                                    user and irq/softirq as system
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn cpu_usage(name: &str, before: &[u64; 8], after: &[u64; 8])-> sp::CpuUsage{
+    let d: Vec<f64> = before.iter().zip(after).map(|(b, a)| a.saturating_sub(*b) as f64).collect();
+    let total: f64 = d.iter().sum();
+    let pct = |v: f64| if total > 0.0 { (v / total * 10000.0).round() / 100.0 } else { 0.0 };
+    sp::CpuUsage {
+        cpu: name.to_string(),
+        user: pct(d[0] + d[1]),
+        system: pct(d[2] + d[5] + d[6]),
+        iowait: pct(d[4]),
+        steal: pct(d[7]),
+        idle: pct(d[3]),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_activity: -input:         /
+                        -output:        a SystemActivity; an Error if /proc/stat is not readable
+                        -description:   read /proc/stat twice, CPU_WINDOW apart, to compute the cpu usage (total and
+                                        per core) over this window, then read the counters of the second reading,
+                                        /proc/loadavg and /proc/uptime
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_activity()-> Result<sp::SystemActivity, std::io::Error>{
+    let before = read_cpu_times(&fs::read_to_string(procfs::path("stat"))?);
+    thread::sleep(CPU_WINDOW);
+    let content = fs::read_to_string(procfs::path("stat"))?;
+    let after = read_cpu_times(&content);
+
+    let mut usages: Vec<sp::CpuUsage> = after.iter()
+        .filter_map(|(name, a)| before.iter().find(|(n, _)| n == name).map(|(_, b)| cpu_usage(name, b, a)))
+        .collect();
+    let cpu_total = if !usages.is_empty() && usages[0].cpu == "cpu" {
+        usages.remove(0)
+    } else {
+        cpu_usage("cpu", &[0; 8], &[0; 8])
+    };
+
+    let counter = |key: &str| content.lines()
+        .find_map(|line| line.strip_prefix(key).and_then(|v| v.trim().parse().ok()))
+        .unwrap_or(0);
+    let loadavg = fs::read_to_string(procfs::path("loadavg")).unwrap_or_default();
+    let mut load_average = [0.0; 3];
+    for (slot, value) in load_average.iter_mut().zip(loadavg.split_whitespace()) {
+        *slot = value.parse().unwrap_or(0.0);
+    }
+
+    Ok(sp::SystemActivity {
+        uptime_seconds: read_uptime(),
+        load_average,
+        context_switches: counter("ctxt "),
+        forks: counter("processes "),
+        procs_running: counter("procs_running "),
+        procs_blocked: counter("procs_blocked "),
+        cpu_total,
+        cpu_cores: usages,
+    })
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_boot_id:  -input:         /
+                        -output:        the random id of the current boot (/proc/sys/kernel/random/boot_id); empty if
+                                        unreadable
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_boot_id() -> String {
+    fs::read_to_string(procfs::path("sys/kernel/random/boot_id")).unwrap_or_default().trim().to_string()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_boot_time:    -input:         /
+                            -output:        the boot time in seconds since the epoch ("btime" of /proc/stat); 0 if
+                                            unreadable
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_boot_time() -> u64 {
+    fs::read_to_string(procfs::path("stat"))
+        .unwrap_or_default()
+        .lines()
+        .find_map(|line| line.strip_prefix("btime ").and_then(|v| v.trim().parse().ok()))
+        .unwrap_or(0)
+}
@@ -0,0 +1,79 @@
+/*
+----------------------------------------------------------------------------------------
+File used to show the threads of a process (from /proc/PID/task) with their own cpu usage,
+last cpu and context switches, to find the thread of a pool that is spinning. Also used by
+live --threads.
+----------------------------------------------------------------------------------------
+*/
+
+use crate::live::TIMESTAMP_FORMAT;
+use crate::output::OutputOptions;
+use chrono::Local;
+use ppsx::struct_proc as sp;
+use ppsx::system::CPU_WINDOW;
+use ppsx::{CpuSampler, Process};
+use std::thread;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_samples:  -input:         a process, the cpu sampler of the threads and the time of the reading
+                        -output:        one ThreadSample per thread of the process (cpu_percent is None for a thread
+                                        read for the first time); an Error if the process is gone
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_samples(process: &Process, sampler: &mut CpuSampler, timestamp: &str) -> Result<Vec<sp::ThreadSample>, ppsx::Error> {
+    Ok(process.threads()?
+        .into_iter()
+        .map(|t| sp::ThreadSample {
+            pid: process.pid(),
+            tid: t.tid,
+            cpu_percent: sampler.sample_ticks(t.tid, t.cpu_ticks).map(|u| (u * 100.0).round() / 100.0),
+            name: t.name,
+            state: t.state,
+            last_cpu: t.last_cpu,
+            voluntary_switches: t.voluntary_switches,
+            nonvoluntary_switches: t.nonvoluntary_switches,
+            timestamp: timestamp.to_string(),
+        })
+        .collect())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render:    -input:         thread samples
+                    -output:        an aligned table with one row per thread
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn render(threads: &[sp::ThreadSample]) -> String {
+    let width = threads.iter().map(|t| t.name.len()).max().unwrap_or(0).max(4);
+    let mut output = format!("{:>7}  {:>7}  {:<width$}  {:^5}  {:>7}  {:>8}  {:>10}  {:>10}\n",
+        "PID", "TID", "NAME", "STATE", "CPU%", "LAST CPU", "VOL CS", "NONVOL CS", width = width);
+    for t in threads {
+        let cpu = t.cpu_percent.map(|c| format!("{:.2}", c)).unwrap_or_else(|| String::from("-"));
+        output.push_str(&format!("{:>7}  {:>7}  {:<width$}  {:^5}  {:>7}  {:>8}  {:>10}  {:>10}\n",
+            t.pid, t.tid, t.name, t.state, cpu, t.last_cpu, t.voluntary_switches, t.nonvoluntary_switches, width = width));
+    }
+    output
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function threads:   -input:         pid of the wanted process and the output options
+                    -output:        Result type (did it succed or not)
+                    -description:   read the threads of the process twice, CPU_WINDOW apart, to get the cpu usage of
+                                    each thread, then display them in the chosen format
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn threads(pid: usize, out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let Ok(process) = Process::new(pid) else {
+        println!("===== PID id not reconized =====");
+        return Ok(());
+    };
+    let mut sampler = CpuSampler::new();
+    read_samples(&process, &mut sampler, "")?;
+    thread::sleep(CPU_WINDOW);
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    // threads started during the window have no cpu usage
+    let threads = read_samples(&process, &mut sampler, &timestamp)?;
+    out.emit(&threads, || format!("===== Threads of PID {} (cpu over {} ms) =====\n{}", pid, CPU_WINDOW.as_millis(), render(&threads)))
+}
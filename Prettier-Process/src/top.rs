@@ -0,0 +1,439 @@
+use ppsx::proc::get_all_info;
+use ppsx::procfs;
+use ppsx::{CpuSampler, Process};
+use crate::signal::{parse_signal, protected_pids, signal_name};
+use ppsx::struct_proc as sp;
+use chrono::Local;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+struct Row {
+    info: sp::ProcessInfo,
+    cpu: f64,
+} // One process and its cpu usage since the previous refresh
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortBy {
+    Cpu,
+    Rss,
+    Pid,
+    Threads,
+    Name,
+}
+
+enum Mode {
+    Normal,
+    Search(String),
+    Signal(String),
+} // What the keyboard is currently editing
+
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Esc,
+    Quit,
+}
+
+struct Top {
+    rows: Vec<Row>,
+    sampler: CpuSampler,
+    last_refresh: Instant,
+    sort: SortBy,
+    reverse: bool,
+    tree: bool,
+    search: String,
+    mode: Mode,
+    selected: Option<usize>, // pid of the selected row
+    offset: usize,
+    message: String,
+} // State of the interactive view
+
+struct RawTerminal {
+    original: libc::termios,
+} // Puts the terminal in raw mode on the alternate screen, restores it when dropped
+
+impl RawTerminal {
+    fn enable() -> Result<RawTerminal, io::Error> {
+        let mut original: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        print!("\x1b[?1049h\x1b[?25l");
+        io::stdout().flush()?;
+        Ok(RawTerminal { original })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function term_size: -input:         /
+                    -output:        the size of the terminal as (rows, columns), (24, 80) if unknown
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn term_size() -> (usize, usize) {
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut ws) } == 0 && ws.ws_row > 0 && ws.ws_col > 0 {
+        (ws.ws_row as usize, ws.ws_col as usize)
+    } else {
+        (24, 80)
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_keys: -input:         the maximum time to wait for a key
+                    -output:        the keys typed during this time (empty if none)
+                    -description:   poll stdin, then decode the bytes read, including the escape sequences of the
+                                    arrows, page up/down, home and end keys
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_keys(timeout: Duration) -> Result<Vec<Key>, io::Error> {
+    let mut fds = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    let ready = unsafe { libc::poll(&mut fds, 1, timeout.as_millis() as i32) };
+    if ready <= 0 {
+        return Ok(Vec::new());
+    }
+    let mut buffer = [0u8; 64];
+    let n = io::stdin().read(&mut buffer)?;
+    let input = String::from_utf8_lossy(&buffer[..n]).to_string();
+
+    let mut keys = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut sequence = String::new();
+                while let Some(&c) = chars.peek() {
+                    chars.next();
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() || c == '~' {
+                        break;
+                    }
+                }
+                match sequence.as_str() {
+                    "A" => Key::Up,
+                    "B" => Key::Down,
+                    "5~" => Key::PageUp,
+                    "6~" => Key::PageDown,
+                    "H" | "1~" | "7~" => Key::Home,
+                    "F" | "4~" | "8~" => Key::End,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            '\x03' => Key::Quit,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            c if !c.is_control() => Key::Char(c),
+            _ => continue,
+        };
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+impl Top {
+    fn new() -> Top {
+        Top {
+            rows: Vec::new(),
+            sampler: CpuSampler::new(),
+            last_refresh: Instant::now(),
+            sort: SortBy::Cpu,
+            reverse: false,
+            tree: false,
+            search: String::new(),
+            mode: Mode::Normal,
+            selected: None,
+            offset: 0,
+            message: String::new(),
+        }
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method refresh: -input:         /
+                    -output:        /
+                    -description:   read every process (get_all_info) and compute its cpu usage since the previous
+                                    refresh with the CpuSampler (0 for new processes)
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn refresh(&mut self) {
+        let rows: Vec<Row> = get_all_info().unwrap_or_default().into_iter().map(|info| {
+            let cpu = Process::new(info.pid).and_then(|p| self.sampler.sample(&p)).ok().flatten().unwrap_or(0.0);
+            Row { info, cpu }
+        }).collect();
+        self.sampler.retain(|pid| rows.iter().any(|r| r.info.pid == pid));
+        self.rows = rows;
+        self.last_refresh = Instant::now();
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method visible: -input:         /
+                    -output:        the rows to display as (index in rows, depth in the tree), after search, sort and
+                                    tree ordering
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn visible(&self) -> Vec<(usize, usize)> {
+        let search = self.search.to_lowercase();
+        let mut order: Vec<usize> = (0..self.rows.len())
+            .filter(|i| {
+                let info = &self.rows[*i].info;
+                search.is_empty() || info.name.to_lowercase().contains(&search) || info.cmdline.to_lowercase().contains(&search)
+            })
+            .collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (&self.rows[*a], &self.rows[*b]);
+            let ord = match self.sort {
+                SortBy::Cpu => b.cpu.partial_cmp(&a.cpu).unwrap_or(std::cmp::Ordering::Equal),
+                SortBy::Rss => b.info.vm_rss.cmp(&a.info.vm_rss),
+                SortBy::Pid => a.info.pid.cmp(&b.info.pid),
+                SortBy::Threads => b.info.threads.cmp(&a.info.threads),
+                SortBy::Name => a.info.name.to_lowercase().cmp(&b.info.name.to_lowercase()),
+            };
+            ord.then(a.info.pid.cmp(&b.info.pid))
+        });
+        if self.reverse {
+            order.reverse();
+        }
+        if !self.tree {
+            return order.into_iter().map(|i| (i, 0)).collect();
+        }
+
+        // Tree view: siblings keep the sort order, processes whose parent is hidden become roots
+        let shown: HashSet<usize> = order.iter().map(|i| self.rows[*i].info.pid).collect();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+        for i in &order {
+            let info = &self.rows[*i].info;
+            if info.ppid != info.pid && shown.contains(&info.ppid) {
+                children.entry(info.ppid).or_default().push(*i);
+            } else {
+                roots.push(*i);
+            }
+        }
+        let mut result = Vec::new();
+        let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+        while let Some((i, depth)) = stack.pop() {
+            result.push((i, depth));
+            if let Some(kids) = children.get(&self.rows[i].info.pid) {
+                stack.extend(kids.iter().rev().map(|k| (*k, depth + 1)));
+            }
+        }
+        result
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method render:  -input:         /
+                    -output:        Result type (did it succed or not)
+                    -description:   redraw the whole screen in place: summary line, column headers, the visible slice
+                                    of the process list (selected row in reverse video) and the prompt/help line
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn render(&mut self) -> Result<(), io::Error> {
+        let (height, width) = term_size();
+        let visible = self.visible();
+        let list_height = height.saturating_sub(4).max(1);
+
+        let mut cursor = self.selected
+            .and_then(|pid| visible.iter().position(|(i, _)| self.rows[*i].info.pid == pid))
+            .unwrap_or(0);
+        cursor = cursor.min(visible.len().saturating_sub(1));
+        self.selected = visible.get(cursor).map(|(i, _)| self.rows[*i].info.pid);
+        if cursor < self.offset {
+            self.offset = cursor;
+        } else if cursor >= self.offset + list_height {
+            self.offset = cursor + 1 - list_height;
+        }
+
+        let fit = |line: String| -> String { line.chars().take(width).collect() };
+        let sort = match self.sort {
+            SortBy::Cpu => "cpu",
+            SortBy::Rss => "rss",
+            SortBy::Pid => "pid",
+            SortBy::Threads => "threads",
+            SortBy::Name => "name",
+        };
+        let total_cpu: f64 = self.rows.iter().map(|r| r.cpu).sum();
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen.push_str(&fit(format!("ppsx top - {} | {} processes ({} shown) | cpu: {:.1}% | sort: {}{}{}{}",
+            Local::now().format("%H:%M:%S"), self.rows.len(), visible.len(), total_cpu, sort,
+            if self.reverse { " (reversed)" } else { "" }, if self.tree { " | tree" } else { "" },
+            if self.search.is_empty() { String::new() } else { format!(" | search: {}", self.search) })));
+        screen.push_str("\r\n\r\n");
+        screen.push_str("\x1b[7m");
+        screen.push_str(&format!("{:<width$}", fit(format!("{:>7} {:>7} {:^5} {:>6} {:>10} {:>7}  COMMAND",
+            "PID", "PPID", "STATE", "CPU%", "RSS(kB)", "THREADS")), width = width));
+        screen.push_str("\x1b[0m\r\n");
+
+        for (line, (i, depth)) in visible.iter().enumerate().skip(self.offset).take(list_height) {
+            let row = &self.rows[*i];
+            let name = if *depth > 0 { format!("{}`- {}", "  ".repeat(depth - 1), row.info.name) } else { row.info.name.clone() };
+            let text = fit(format!("{:>7} {:>7} {:^5} {:>6.1} {:>10} {:>7}  {}",
+                row.info.pid, row.info.ppid, row.info.state, row.cpu, row.info.vm_rss, row.info.threads, name));
+            if line == cursor {
+                screen.push_str(&format!("\x1b[7m{:<width$}\x1b[0m\r\n", text, width = width));
+            } else {
+                screen.push_str(&text);
+                screen.push_str("\r\n");
+            }
+        }
+
+        screen.push_str(&format!("\x1b[{};1H", height));
+        let bottom = match &self.mode {
+            Mode::Search(text) => format!("Search: {}", text),
+            Mode::Signal(text) => format!("Signal to send to PID {} [TERM]: {}", self.selected.unwrap_or(0), text),
+            Mode::Normal if !self.message.is_empty() => self.message.clone(),
+            Mode::Normal => String::from("q:quit  c/m/p/h/n:sort cpu/rss/pid/threads/name  r:reverse  t:tree  /:search  k:signal  arrows/PgUp/PgDn:scroll"),
+        };
+        screen.push_str(&fit(bottom));
+        print!("{}", screen);
+        io::stdout().flush()
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method move_cursor: -input:         the number of rows to move (negative goes up)
+                        -output:        /
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn move_cursor(&mut self, delta: isize) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            return;
+        }
+        let cursor = self.selected
+            .and_then(|pid| visible.iter().position(|(i, _)| self.rows[*i].info.pid == pid))
+            .unwrap_or(0);
+        let target = (cursor as isize + delta).clamp(0, visible.len() as isize - 1) as usize;
+        self.selected = Some(self.rows[visible[target].0].info.pid);
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method send_signal: -input:         the signal typed by the user (TERM if empty)
+                        -output:        /
+                        -description:   send the signal to the selected process and keep the result as message;
+                                        PID 1, ppsx and its ancestors are refused as by the signal command
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn send_signal(&mut self, text: &str) {
+        let Some(pid) = self.selected else { return };
+        let text = if text.trim().is_empty() { "TERM" } else { text.trim() };
+        self.message = match parse_signal(text) {
+            None => format!("Unknown signal: {}", text),
+            Some(_) if !procfs::is_live() => String::from("Cannot send signals: --proc-root is not the live /proc"),
+            Some(sig) => match protected_pids().get(&pid) {
+                Some(reason) => format!("Refusing to send {} to PID {} ({})", signal_name(sig), pid, reason),
+                None if unsafe { libc::kill(pid as libc::pid_t, sig) } == 0 => format!("Sent {} to PID {}", signal_name(sig), pid),
+                None => format!("Cannot send {} to PID {}: {}", signal_name(sig), pid, io::Error::last_os_error()),
+            },
+        };
+    }
+
+    /*
+    --------------------------------------------------------------------------------------------------------------------
+    Method handle:  -input:         a key typed by the user
+                    -output:        true if the user asked to quit
+    --------------------------------------------------------------------------------------------------------------------
+    */
+    fn handle(&mut self, key: Key) -> bool {
+        let page = term_size().0.saturating_sub(4).max(1) as isize;
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Search(mut text) => match key {
+                Key::Enter => {}
+                Key::Esc => { self.search.clear(); }
+                Key::Quit => return true,
+                Key::Backspace => { text.pop(); self.search = text.clone(); self.mode = Mode::Search(text); }
+                Key::Char(c) => { text.push(c); self.search = text.clone(); self.mode = Mode::Search(text); }
+                _ => { self.mode = Mode::Search(text); }
+            },
+            Mode::Signal(mut text) => match key {
+                Key::Enter => self.send_signal(&text),
+                Key::Esc => {}
+                Key::Quit => return true,
+                Key::Backspace => { text.pop(); self.mode = Mode::Signal(text); }
+                Key::Char(c) => { text.push(c); self.mode = Mode::Signal(text); }
+                _ => { self.mode = Mode::Signal(text); }
+            },
+            Mode::Normal => {
+                self.message.clear();
+                match key {
+                    Key::Quit | Key::Char('q') => return true,
+                    Key::Char('c') => self.sort = SortBy::Cpu,
+                    Key::Char('m') => self.sort = SortBy::Rss,
+                    Key::Char('p') => self.sort = SortBy::Pid,
+                    Key::Char('h') => self.sort = SortBy::Threads,
+                    Key::Char('n') => self.sort = SortBy::Name,
+                    Key::Char('r') => self.reverse = !self.reverse,
+                    Key::Char('t') => self.tree = !self.tree,
+                    Key::Char('/') => self.mode = Mode::Search(self.search.clone()),
+                    Key::Char('k') if self.selected.is_some() => self.mode = Mode::Signal(String::new()),
+                    Key::Esc => self.search.clear(),
+                    Key::Up => self.move_cursor(-1),
+                    Key::Down => self.move_cursor(1),
+                    Key::PageUp => self.move_cursor(-page),
+                    Key::PageDown => self.move_cursor(page),
+                    Key::Home => self.move_cursor(isize::MIN / 2),
+                    Key::End => self.move_cursor(isize::MAX / 2),
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function start: -input:         /
+                -output:        Result type (did it succed or not)
+                -description:   start the full-screen interactive view of all processes: refresh the process list
+                                every second, redraw it in place and react to the keys in between
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn start() -> Result<(), Box<dyn std::error::Error>> {
+    if unsafe { libc::isatty(libc::STDIN_FILENO) } == 0 || unsafe { libc::isatty(libc::STDOUT_FILENO) } == 0 {
+        return Err("top needs an interactive terminal".into());
+    }
+    let mut top = Top::new();
+    top.refresh();
+    let _terminal = RawTerminal::enable()?;
+    loop {
+        if top.last_refresh.elapsed() >= REFRESH {
+            top.refresh();
+        }
+        top.render()?;
+        let timeout = REFRESH.saturating_sub(top.last_refresh.elapsed());
+        for key in read_keys(timeout)? {
+            if top.handle(key) {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::output::{Format, OutputOptions};
+use ppsx::proc::{get_all_info, read_uptime};
+use ppsx::stat::{lifetime_cpu_percent, read_stat};
+use ppsx::struct_proc as sp;
+use std::collections::HashMap;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function build_node:    -input:         a pid, the known processes, the children of every pid, the columns to fill and
+                                        the system uptime
+                        -output:        the TreeNode of this pid with all its descendants
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn build_node(pid: usize, procs: &HashMap<usize, sp::ProcessInfo>, children: &HashMap<usize, Vec<usize>>,
+              columns: (bool, bool, bool), uptime: f64) -> sp::TreeNode {
+    let (rss, cpu, threads) = columns;
+    let info = &procs[&pid];
+    let kids = children.get(&pid)
+        .map(|kids| kids.iter().map(|k| build_node(*k, procs, children, columns, uptime)).collect())
+        .unwrap_or_default();
+    sp::TreeNode {
+        pid,
+        name: info.name.clone(),
+        state: info.state.clone(),
+        threads: if threads { Some(info.threads) } else { None },
+        vm_rss: if rss { Some(info.vm_rss) } else { None },
+        cpu_percent: if cpu { read_stat(pid).ok().map(|s| lifetime_cpu_percent(&s, uptime)) } else { None },
+        children: kids,
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function build_tree:    -input:         an optional root pid and the columns to fill (rss, cpu, threads)
+                        -output:        the roots of the process hierarchy (one root if a pid is given)
+                        -description:   read every process (get_all_info), group them by parent pid, then build the
+                                        subtree of the given pid or, without pid, of every process whose parent is
+                                        unknown (init, kthreadd...)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn build_tree(pid: Option<usize>, columns: (bool, bool, bool)) -> Result<Vec<sp::TreeNode>, std::io::Error> {
+    let procs: HashMap<usize, sp::ProcessInfo> = get_all_info()?.into_iter().map(|p| (p.pid, p)).collect();
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    for p in procs.values() {
+        if p.ppid != p.pid {
+            children.entry(p.ppid).or_default().push(p.pid);
+        }
+    }
+    for kids in children.values_mut() {
+        kids.sort();
+    }
+    let uptime = read_uptime();
+
+    let mut roots: Vec<usize> = match pid {
+        Some(pid) => if procs.contains_key(&pid) { vec![pid] } else { Vec::new() },
+        None => procs.values().filter(|p| !procs.contains_key(&p.ppid) || p.ppid == p.pid).map(|p| p.pid).collect(),
+    };
+    roots.sort();
+    Ok(roots.into_iter().map(|r| build_node(r, &procs, &children, columns, uptime)).collect())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function render_node:   -input:         a TreeNode, the prefix of its line, the prefix of its children lines and the
+                                        output string
+                        -output:        /
+                        -description:   append the node and (recursively) its children to the output as an ASCII tree
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn render_node(node: &sp::TreeNode, prefix: &str, child_prefix: &str, output: &mut String) {
+    let mut extra = Vec::new();
+    if let Some(rss) = node.vm_rss { extra.push(format!("rss: {} kB", rss)); }
+    if let Some(cpu) = node.cpu_percent { extra.push(format!("cpu: {:.2}%", cpu)); }
+    if let Some(threads) = node.threads { extra.push(format!("threads: {}", threads)); }
+    if extra.is_empty() {
+        output.push_str(&format!("{}{} {} [{}]\n", prefix, node.pid, node.name, node.state));
+    } else {
+        output.push_str(&format!("{}{} {} [{}] ({})\n", prefix, node.pid, node.name, node.state, extra.join(", ")));
+    }
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let (branch, next) = if last { ("`-- ", "    ") } else { ("|-- ", "|   ") };
+        render_node(child, &format!("{}{}", child_prefix, branch), &format!("{}{}", child_prefix, next), output);
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function flatten_node:  -input:         a TreeNode, the pid of its parent, its depth and the output rows
+                        -output:        /
+                        -description:   append one row per node (the node first, then recursively its children) with
+                                        its depth and parent pid, for the formats without nesting (table, csv)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn flatten_node(node: &sp::TreeNode, ppid: Option<usize>, depth: usize, rows: &mut Vec<serde_json::Value>) {
+    let mut row = serde_json::json!({"depth": depth, "ppid": ppid, "pid": node.pid, "name": node.name, "state": node.state});
+    if let Some(rss) = node.vm_rss { row["vm_rss"] = rss.into(); }
+    if let Some(cpu) = node.cpu_percent { row["cpu_percent"] = cpu.into(); }
+    if let Some(threads) = node.threads { row["threads"] = threads.into(); }
+    rows.push(row);
+    for child in &node.children {
+        flatten_node(child, Some(node.pid), depth + 1, rows);
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function tree:  -input:         an optional root pid, the optional columns (rss, cpu, threads) and the output options
+                -output:        Result type (did it succed or not)
+                -description:   build the process hierarchy (whole system or subtree of the pid) and display it as
+                                an indented ASCII tree, as nested objects ("children" arrays) in json and yaml, or
+                                as one row per process in table, csv and ndjson
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn tree(pid: Option<usize>, columns: (bool, bool, bool), out: &OutputOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let roots = build_tree(pid, columns)?;
+    if roots.is_empty() {
+        println!("===== PID id not reconized =====");
+        return Ok(());
+    }
+
+    let text = || {
+        let mut output = String::new();
+        for root in &roots {
+            render_node(root, "", "", &mut output);
+        }
+        output
+    };
+    match out.format() {
+        Format::Table | Format::Csv | Format::Ndjson => {
+            let mut rows = Vec::new();
+            for root in &roots {
+                flatten_node(root, None, 0, &mut rows);
+            }
+            out.emit(&rows, text)
+        }
+        _ if pid.is_some() => out.emit(&roots[0], text),
+        _ => out.emit(&roots, text),
+    }
+}
@@ -0,0 +1,205 @@
+/*
+----------------------------------------------------------------------------------------
+File used to change the scheduling knobs of a process: nice value, I/O priority and CPU
+affinity. Linux applies them per thread, so every command can either target the given
+thread id only or every thread of its thread group (--all-threads).
+----------------------------------------------------------------------------------------
+*/
+
+use clap::ValueEnum;
+use ppsx::proc::{cpu_affinity, io_priority, IOPRIO_CLASS_SHIFT, IOPRIO_WHO_PROCESS};
+use ppsx::procfs;
+use std::fs;
+use std::io;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum IoClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function thread_ids:    -input:         a process id and option as boolean (all_threads)
+                        -output:        the thread ids to change: every entry of /proc/{PID}/task, or only the pid; an
+                                        Error if the procfs root is not the live /proc (the syscalls would change
+                                        another process)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn thread_ids(pid: usize, all_threads: bool) -> Result<Vec<usize>, io::Error> {
+    if !procfs::is_live() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("cannot act on PID {}: --proc-root is not the live /proc", pid)));
+    }
+    if !procfs::pid_path(pid, "stat").exists() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("no process with PID {}", pid)));
+    }
+    if !all_threads {
+        return Ok(vec![pid]);
+    }
+    let mut tids: Vec<usize> = fs::read_dir(procfs::pid_path(pid, "task"))?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter_map(|name| name.parse().ok())
+        .collect();
+    tids.sort();
+    Ok(tids)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function parse_cpu_list:    -input:         a list of cpus as in /proc/{PID}/status (ex: "0-3,6")
+                            -output:        the cpu numbers; an Error message if malformed or if a cpu does not fit in
+                                            a cpu_set_t (checked before expanding a range)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn parse_cpu_list(list: &str) -> Result<Vec<usize>, String> {
+    let mut cpus = Vec::new();
+    for part in list.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let invalid = || format!("invalid cpu list: {}", list);
+        let (a, b): (usize, usize) = match part.split_once('-') {
+            Some((a, b)) => (a.parse().map_err(|_| invalid())?, b.parse().map_err(|_| invalid())?),
+            None => {
+                let cpu = part.parse().map_err(|_| invalid())?;
+                (cpu, cpu)
+            }
+        };
+        if a > b {
+            return Err(invalid());
+        }
+        if b >= libc::CPU_SETSIZE as usize {
+            return Err(format!("cpu {} is out of range (at most {})", b, libc::CPU_SETSIZE - 1));
+        }
+        cpus.extend(a..=b);
+    }
+    if cpus.is_empty() {
+        return Err(format!("empty cpu list: {}", list));
+    }
+    Ok(cpus)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function renice:    -input:         a pid, the new nice value and option as boolean (all_threads)
+                    -output:        Result type (did it succed or not)
+                    -description:   change the nice value of the thread(s) with setpriority(2) and show the old and new
+                                    values
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn renice(pid: usize, nice: i32, all_threads: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !(-20..=19).contains(&nice) {
+        return Err(format!("nice value must be between -20 and 19, got {}", nice).into());
+    }
+    for tid in thread_ids(pid, all_threads)? {
+        // -1 is also a valid nice value: only errno tells a failure apart
+        let old = unsafe {
+            *libc::__errno_location() = 0;
+            libc::getpriority(libc::PRIO_PROCESS, tid as libc::id_t)
+        };
+        let error = io::Error::last_os_error();
+        if old == -1 && error.raw_os_error() != Some(0) {
+            return Err(format!("cannot read the nice value of {}: {}", tid, error).into());
+        }
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) } != 0 {
+            return Err(format!("cannot renice {}: {}", tid, io::Error::last_os_error()).into());
+        }
+        println!("{} {}: nice {} -> {}", if tid == pid { "PID" } else { "TID" }, tid, old, nice);
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function ionice:    -input:         a pid, the new I/O class and level (None to only show the current priority) and
+                                    option as boolean (all_threads)
+                    -output:        Result type (did it succed or not)
+                    -description:   change the I/O priority of the thread(s) with ioprio_set(2); the level (0 highest,
+                                    7 lowest) defaults to 4 and is ignored by the idle class
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn ionice(pid: usize, class: Option<IoClass>, level: Option<u8>, all_threads: bool) -> Result<(), Box<dyn std::error::Error>> {
+    for tid in thread_ids(pid, all_threads)? {
+        let label = if tid == pid { "PID" } else { "TID" };
+        let old = io_priority(tid);
+        let Some(class) = class else {
+            println!("{} {}: I/O priority {}", label, tid, old);
+            continue;
+        };
+        let level = level.unwrap_or(4);
+        if level > 7 {
+            return Err(format!("I/O level must be between 0 and 7, got {}", level).into());
+        }
+        let prio = match class {
+            IoClass::Realtime => (1 << IOPRIO_CLASS_SHIFT) | level as i32,
+            IoClass::BestEffort => (2 << IOPRIO_CLASS_SHIFT) | level as i32,
+            IoClass::Idle => 3 << IOPRIO_CLASS_SHIFT,
+        };
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid as libc::c_int, prio) } < 0 {
+            return Err(format!("cannot change the I/O priority of {}: {}", tid, io::Error::last_os_error()).into());
+        }
+        println!("{} {}: I/O priority {} -> {}", label, tid, old, io_priority(tid));
+    }
+    Ok(())
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function affinity:  -input:         a pid, the new cpu list (None to only show the current one) and option as boolean
+                                    (all_threads)
+                    -output:        Result type (did it succed or not)
+                    -description:   pin the thread(s) to the given cpus with sched_setaffinity(2)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn affinity(pid: usize, cpus: Option<String>, all_threads: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let cpus = match cpus {
+        Some(list) => Some(parse_cpu_list(&list)?),
+        None => None,
+    };
+    for tid in thread_ids(pid, all_threads)? {
+        let label = if tid == pid { "PID" } else { "TID" };
+        let old = cpu_affinity(tid);
+        let Some(cpus) = &cpus else {
+            println!("{} {}: CPU affinity {}", label, tid, old);
+            continue;
+        };
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in cpus {
+            unsafe { libc::CPU_SET(*cpu, &mut set) };
+        }
+        if unsafe { libc::sched_setaffinity(tid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+            return Err(format!("cannot change the CPU affinity of {}: {}", tid, io::Error::last_os_error()).into());
+        }
+        println!("{} {}: CPU affinity {} -> {}", label, tid, old, cpu_affinity(tid));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_lists() {
+        assert_eq!(parse_cpu_list("3"), Ok(vec![3]));
+        assert_eq!(parse_cpu_list("0-3,6"), Ok(vec![0, 1, 2, 3, 6]));
+        assert_eq!(parse_cpu_list(" 1 , 4-5 ,"), Ok(vec![1, 4, 5]));
+    }
+
+    #[test]
+    fn invalid_cpu_lists() {
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("a").is_err());
+        assert!(parse_cpu_list("0-").is_err());
+        assert!(parse_cpu_list("-1").is_err());
+    }
+
+    #[test]
+    fn out_of_range_cpus() {
+        let last = libc::CPU_SETSIZE as usize - 1;
+        assert_eq!(parse_cpu_list(&format!("{}", last)), Ok(vec![last]));
+        assert!(parse_cpu_list(&format!("{}", last + 1)).is_err());
+        assert!(parse_cpu_list("0-18446744073709551615").is_err());
+        assert!(parse_cpu_list(&format!("{}-{}", usize::MAX - 1, usize::MAX)).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+/*
+----------------------------------------------------------------------------------------
+File used to read the identity of processes: the real, effective, saved and filesystem
+uids and gids and the supplementary groups of /proc/{PID}/status, named after the users
+of /etc/passwd and the groups of /etc/group. With another procfs root (--proc-root), the
+etc directory next to it is used when it exists (ex: /host/etc for /host/proc).
+----------------------------------------------------------------------------------------
+*/
+
+use crate::procfs;
+use crate::struct_proc as sp;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+static GROUPS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function etc_path:  -input:         the name of a file of /etc (ex: "passwd")
+                    -output:        the file in the etc directory next to the procfs root if it exists, in /etc else
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn etc_path(file: &str) -> PathBuf {
+    match procfs::root().parent().map(|dir| dir.join("etc").join(file)) {
+        Some(path) if path.exists() => path,
+        _ => Path::new("/etc").join(file),
+    }
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_names:    -input:         the name of a file of /etc in the passwd format ("NAME:PASSWORD:ID:...")
+                        -output:        the name of each id of the file (the first one if an id is listed twice)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn read_names(file: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in fs::read_to_string(etc_path(file)).unwrap_or_default().lines() {
+        let parts: Vec<&str> = line.split(':').collect();
+        if let Some(id) = parts.get(2).and_then(|id| id.parse().ok()) {
+            names.entry(id).or_insert_with(|| parts[0].to_string());
+        }
+    }
+    names
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_users:    -input:         /
+                        -output:        the user name of each uid of /etc/passwd, read again at each call
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_users() -> HashMap<u32, String> {
+    read_names("passwd")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_groups:   -input:         /
+                        -output:        the group name of each gid of /etc/group, read again at each call
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_groups() -> HashMap<u32, String> {
+    read_names("group")
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function user_name: -input:         a uid
+                    -output:        its user name; None if unknown (/etc/passwd is read once per run)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn user_name(uid: u32) -> Option<String> {
+    USERS.get_or_init(read_users).get(&uid).cloned()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function group_name:    -input:         a gid
+                        -output:        its group name; None if unknown (/etc/group is read once per run)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn group_name(gid: u32) -> Option<String> {
+    GROUPS.get_or_init(read_groups).get(&gid).cloned()
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve:   -input:         the names of a database and a name or a numerical id
+                    -output:        the id if it is a number or a name of the database; None else
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn resolve(names: &HashMap<u32, String>, value: &str) -> Option<u32> {
+    if let Ok(id) = value.parse() {
+        return Some(id);
+    }
+    names.iter().find(|(_, name)| name.as_str() == value).map(|(id, _)| *id)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve_uid:   -input:         a user name or a numerical uid
+                        -output:        the matching uid if it is a number or a user of /etc/passwd; None else
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn resolve_uid(user: &str) -> Option<u32> {
+    resolve(&read_users(), user)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function resolve_gid:   -input:         a group name or a numerical gid
+                        -output:        the matching gid if it is a number or a group of /etc/group; None else
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn resolve_gid(group: &str) -> Option<u32> {
+    resolve(&read_groups(), group)
+}
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function read_credentials:  -input:         a process id
+                            -output:        the Credentials of the process, with the names of its ids; an Error if
+                                            /proc/{PID}/status is not readable
+                            -description:   the "Uid:" and "Gid:" lines hold the real, effective, saved and filesystem
+                                            ids, "Groups:" the supplementary groups; the process is flagged setid when
+                                            its effective uid or gid is not the real one (setuid/setgid binary...)
+------------------------------------------------------------------------------------------------------------------------
+*/
+pub fn read_credentials(pid: usize) -> Result<sp::Credentials, io::Error> {
+    let status = fs::read_to_string(procfs::pid_path(pid, "status"))?;
+    let ids = |key: &str| -> Vec<u32> {
+        status.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|values| values.split_whitespace().filter_map(|v| v.parse().ok()).collect())
+            .unwrap_or_default()
+    };
+    let uids = ids("Uid:");
+    let gids = ids("Gid:");
+    let user = |i: usize| {
+        let id = uids.get(i).copied().unwrap_or(0);
+        sp::NamedId { id, name: user_name(id) }
+    };
+    let group = |id: u32| sp::NamedId { id, name: group_name(id) };
+    let gid = |i: usize| group(gids.get(i).copied().unwrap_or(0));
+
+    Ok(sp::Credentials {
+        real_uid: user(0),
+        effective_uid: user(1),
+        saved_uid: user(2),
+        fs_uid: user(3),
+        real_gid: gid(0),
+        effective_gid: gid(1),
+        saved_gid: gid(2),
+        fs_gid: gid(3),
+        groups: ids("Groups:").into_iter().map(group).collect(),
+        setid: uids.get(1) != uids.first() || gids.get(1) != gids.first(),
+    })
+}
@@ -0,0 +1,130 @@
+/*
+----------------------------------------------------------------------------------------
+File used to check the readers against the recorded /proc tree of tests/fixtures/proc
+(one process, 1000, a tmux server with a TCP, a TCP6 and a Unix socket, plus the mountinfo
+of PID 1) and the /sys tree next to it (its cgroup and the name of one block device).
+----------------------------------------------------------------------------------------
+*/
+
+use ppsx::{cgroup, mem, net, proc, procfs};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+const PID: usize = 1000;
+
+/*
+------------------------------------------------------------------------------------------------------------------------
+Function fixture_root:  -input:         /
+                        -output:        /
+                        -description:   use the fixture tree as procfs root (every test sets the same root, only the
+                                        first call has an effect)
+------------------------------------------------------------------------------------------------------------------------
+*/
+fn fixture_root() {
+    procfs::set_root(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc"));
+}
+
+#[test]
+fn read_info_from_fixture() {
+    fixture_root();
+    let info = proc::read_info(PID).unwrap();
+    assert_eq!(info.pid, PID);
+    assert_eq!(info.name, "tmux: server");
+    assert_eq!(info.state, "S");
+    assert_eq!(info.ppid, 1);
+    assert_eq!(info.uid, 1000);
+    assert_eq!(info.gid, 100);
+    assert_eq!((info.euid, info.egid), (1000, 100));
+    assert_eq!(info.threads, 1);
+    assert_eq!(info.vm_size, 11000);
+    assert_eq!(info.vm_rss, 4096);
+    assert_eq!(info.cmdline, "tmux new-session -d");
+    assert!(proc::read_info(4242).is_err());
+}
+
+#[test]
+fn read_all_info_from_fixture() {
+    fixture_root();
+    let info = proc::read_all_info(PID).unwrap();
+    assert_eq!(info.name, "tmux: server");
+    assert_eq!((info.utime, info.stime, info.sttime), (25, 10, 5000));
+    assert_eq!(info.blkio_ticks, 7);
+    assert_eq!((info.vm_data, info.vm_stack, info.vm_hwm, info.vm_peak), (1200, 132, 4500, 12000));
+    assert_eq!(info.read_bytes, Some(40960));
+    assert_eq!(info.write_count, Some(120));
+    assert_eq!(info.fd_count, 4);
+    assert_eq!(info.cwd, "/home/user");
+    assert_eq!(info.exe, "/usr/bin/tmux");
+    assert_eq!(info.uptime, 12345);
+    assert_eq!(info.cgroups, vec!["0::/user.slice/user-1000.slice/session-1.scope"]);
+    assert_eq!(info.tcp_connections.len(), 2);
+    assert_eq!(info.unix_sockets.len(), 1);
+    assert!(info.udp_connections.is_empty());
+    assert_eq!(info.credentials.real_uid.id, 1000);
+    assert_eq!(info.credentials.groups.iter().map(|g| g.id).collect::<Vec<u32>>(), vec![100, 27]);
+    assert!(!info.credentials.setid);
+}
+
+#[test]
+fn mem_from_fixture() {
+    fixture_root();
+    let rollup = mem::read_smaps_rollup(PID).unwrap();
+    assert_eq!((rollup.rss, rollup.pss, rollup.uss), (4096, 2500, 1996));
+    assert_eq!((rollup.swap, rollup.swap_pss), (16, 8));
+    let meminfo = mem::read_meminfo();
+    assert_eq!(meminfo.mem_total, 8048576);
+    assert_eq!(meminfo.mem_available, 5000000);
+    assert_eq!(meminfo.hugepage_size, 2048);
+}
+
+#[test]
+fn sockets_from_fixture() {
+    fixture_root();
+    let inodes = net::socket_inodes(PID);
+    assert_eq!(inodes, HashSet::from([5001, 5002, 5003]));
+
+    // the second line of tcp belongs to another process (inode 9999)
+    let tcp = net::read_inet(PID, "tcp", &inodes);
+    assert_eq!(tcp.len(), 1);
+    assert_eq!((tcp[0].local_address.as_str(), tcp[0].local_port), ("127.0.0.1", 8080));
+    assert_eq!((tcp[0].remote_address.as_str(), tcp[0].remote_port), ("0.0.0.0", 0));
+    assert_eq!(tcp[0].state, "LISTEN");
+
+    let tcp6 = net::read_inet(PID, "tcp6", &inodes);
+    assert_eq!(tcp6.len(), 1);
+    assert_eq!((tcp6[0].local_address.as_str(), tcp6[0].local_port), ("::1", 22));
+
+    let unix = net::read_unix(PID, &inodes);
+    assert_eq!(unix.len(), 1);
+    assert_eq!(unix[0].path.as_deref(), Some("/tmp/tmux-1000/default"));
+    assert!(unix[0].listening);
+    assert_eq!(unix[0].socket_type, "STREAM");
+}
+
+#[test]
+fn no_syscall_outside_live_proc() {
+    fixture_root();
+    assert!(!procfs::is_live());
+    // ioprio_get would read the live process that has the same pid
+    assert_eq!(proc::io_priority(PID), "N/A");
+    assert_eq!(proc::read_all_info(PID).unwrap().io_priority, "N/A");
+}
+
+#[test]
+fn cgroup_from_fixture() {
+    fixture_root();
+    // the cgroup2 mount of PID 1 of the fixture, not the one of the host
+    let sys = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sys");
+    assert_eq!(cgroup::mount_point(), sys.join("fs/cgroup"));
+
+    let info = proc::read_all_info(PID).unwrap().cgroup.unwrap();
+    assert_eq!(info.path, "/user.slice/user-1000.slice/session-1.scope");
+    assert_eq!(info.memory_current, Some(8388608));
+    assert_eq!(info.memory_max.as_deref(), Some("max"));
+    assert_eq!(info.cpu_limit, Some(0.5));
+    assert_eq!((info.nr_throttled, info.throttled_usec), (Some(3), Some(12000)));
+    assert_eq!((info.pids_current, info.pids_max.as_deref()), (Some(2), Some("100")));
+    assert_eq!(info.io.len(), 1);
+    assert_eq!(info.io[0].device, "sda");
+    assert_eq!((info.io[0].read_bytes, info.io[0].write_ios), (40960, 2));
+}